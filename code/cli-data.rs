@@ -1,17 +1,35 @@
 // cli.rs
 use std::io;
+use std::io::prelude::*;
+use std::fs::File;
+use std::path::Path;
 use std::collections::HashMap;
+use std::time::Instant;
 
 type CliResult = Result<String,String>;
 
 struct Cli<'a,D> {
     data: D,
-    callbacks: HashMap<String, Box<Fn(&mut D,&[&str])->CliResult + 'a>>
+    callbacks: HashMap<String, Box<Fn(&mut D,&[&str])->CliResult + 'a>>,
+    describer: Option<Box<Fn(&D)->String + 'a>>,
+    timing: bool,
+    history: Vec<String>
 }
 
 impl <'a,D: Sized> Cli<'a,D> {
     fn new(data: D) -> Cli<'a,D> {
-        Cli{data: data, callbacks: HashMap::new()}
+        Cli{data: data, callbacks: HashMap::new(), describer: None, timing: false, history: Vec::new()}
+    }
+
+    // when on, each result ends with how long the command took
+    fn timing(&mut self, on: bool) {
+        self.timing = on;
+    }
+
+    // the built-in 'state' command shows the data using this
+    fn describe<F>(&mut self, describer: F)
+    where F: Fn(&D)->String + 'a {
+        self.describer = Some(Box::new(describer));
     }
 
     fn cmd<F>(&mut self, name: &str, callback: F)
@@ -19,9 +37,57 @@ impl <'a,D: Sized> Cli<'a,D> {
         self.callbacks.insert(name.to_string(),Box::new(callback));
     }
 
+    // every non-empty line given to process, oldest first
+    fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    // one command per line
+    fn save_history<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        for line in &self.history {
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    // replaces the current history
+    fn load_history<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = io::BufReader::new(File::open(path)?);
+        let mut history = Vec::new();
+        for line in file.lines() {
+            history.push(line?);
+        }
+        self.history = history;
+        Ok(())
+    }
+
     fn process(&mut self,line: &str) -> CliResult {
+        let line = line.trim();
+        if ! line.is_empty() {
+            self.history.push(line.to_string());
+        }
+        if ! self.timing {
+            return self.run(line);
+        }
+        let start = Instant::now();
+        let res = self.run(line);
+        let took = format!(" (took {:.1?})", start.elapsed());
+        match res {
+            Ok(s) => Ok(s + &took),
+            Err(e) => Err(e + &took)
+        }
+    }
+
+    fn run(&mut self,line: &str) -> CliResult {
         let parts: Vec<_> = line.split_whitespace().collect();
         if parts.len() == 0 { return Ok("".to_string()); }
+        if parts[0] == "state" {
+            return match self.describer {
+                Some(ref describer) => Ok(describer(&self.data)),
+                None => Err("no describer for state".to_string())
+            };
+        }
         match self.callbacks.get(parts[0]) {
             Some(callback) => callback(&mut self.data,&parts[1..]),
             None => Err("no such command".to_string())
@@ -77,5 +143,27 @@ fn main() {
         ok(data.answer)
     });
 
+    assert_eq!(cli.process("state"), err("no describer for state"));
+    cli.describe(|data| format!("answer is {}", data.answer));
+    assert_eq!(cli.process("state"), ok("answer is 42"));
+    assert_eq!(cli.process("go 10"), ok(10));
+    assert_eq!(cli.process("state"), ok("answer is 10"));
+
+    cli.timing(true);
+    let timed = cli.process("state").unwrap();
+    assert!(timed.starts_with("answer is 10 (took "));
+    assert!(timed.ends_with("s)"));
+    assert!(cli.process("nonesuch").unwrap_err().contains(" (took "));
+    cli.timing(false);
+
+    assert_eq!(cli.process("  "), ok(""));
+    assert_eq!(cli.history(), ["state", "state", "go 10", "state", "state", "nonesuch"]);
+    let path = std::env::temp_dir().join("cli-history.txt");
+    cli.save_history(&path).expect("can't save history");
+    let mut fresh = Cli::new(Data{answer: 0});
+    fresh.load_history(&path).expect("can't load history");
+    assert_eq!(fresh.history(), cli.history());
+    std::fs::remove_file(&path).expect("can't remove history");
+
     cli.go();
 }
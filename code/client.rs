@@ -1,9 +1,54 @@
 // client.rs
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::env;
+use std::process;
+use std::thread;
+
+// send a line to the echo server at `addr`, and read the echo back.
+// The echo server keeps the connection open, so just read our line back
+fn echo(addr: &str, msg: &str) -> io::Result<String> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(stream,"{}\n",msg)?;
+    let mut rdr = io::BufReader::new(stream);
+    let mut resp = String::new();
+    rdr.read_line(&mut resp)?;
+    Ok(resp.trim_end().to_string())
+}
+
+// a one-line echo server on a port chosen by the system
+fn check_echo() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+    let addr = listener.local_addr().expect("no local address").to_string();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("could not accept");
+        let mut out = stream.try_clone().expect("could not clone");
+        let mut line = String::new();
+        io::BufReader::new(stream).read_line(&mut line).expect("could not read");
+        out.write_all(line.as_bytes()).expect("could not write");
+    });
+    assert_eq!(echo(&addr, "testing 1 2 3").expect("echo failed"), "testing 1 2 3");
+    server.join().unwrap();
+}
 
 fn main() {
-    let mut stream = TcpStream::connect("127.0.0.1:8000").expect("connection failed");
+    check_echo();
+
+    // client [--addr host:port] [message...]
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mut addr = "127.0.0.1:8000".to_string();
+    if args.len() > 1 && args[0] == "--addr" {
+        addr = args.remove(1);
+        args.remove(0);
+    }
+    let msg = if args.len() > 0 { args.join(" ") } else { "hello from the client!".to_string() };
 
-  //  write!(stream,"hello from the client!\n").expect("write failed");
- }
+    match echo(&addr, &msg) {
+        Ok(resp) => println!("echoed: {}",resp),
+        Err(e) => {
+            println!("cannot talk to echo server at {}: {}",addr,e);
+            process::exit(1);
+        }
+    }
+}
@@ -1,12 +1,14 @@
 // client_echo.rs
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::net::{TcpStream, Shutdown};
 
 fn main() {
     let mut stream = TcpStream::connect("127.0.0.1:8000").expect("connection failed");
     let msg = "hello from the client!";
 
     write!(stream,"{}\n",msg).expect("write failed");
+    // the server echoes until we hang up, so say we have nothing more to send
+    stream.shutdown(Shutdown::Write).expect("shutdown failed");
 
     let mut resp = String::new();
     stream.read_to_string(&mut resp).expect("read failed");
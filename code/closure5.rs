@@ -1,29 +1,93 @@
 // closure5.rs
 
+// Rather than adding incr each time, which accumulates rounding error,
+// we work out how many steps there are and compute each value from its index.
+// Then both ends agree on where the range stops.
 struct FRange {
-    val: f64,
-    end: f64,
-    incr: f64
+    start: f64,
+    incr: f64,
+    front: usize,
+    back: usize,
+    // an index whose value must come out exactly, whatever the rounding
+    exact: Option<(usize,f64)>
 }
 
+// a negative skip counts down, so range(1.0,0.0,-0.1) starts at 1.0.
+// Either way the number of steps comes out positive (or the range is empty).
+// A zero skip would never get anywhere. That's a programming error, like indexing
+// past the end of a slice, so we panic rather than make every caller unwrap a Result.
 fn range(x1: f64, x2: f64, skip: f64) -> FRange {
-    FRange {val: x1, end: x2, incr: skip}
+    if skip == 0.0 {
+        panic!("range: increment must not be zero");
+    }
+    let steps = ((x2 - x1)/skip).ceil();
+    let steps = if steps > 0.0 { steps as usize } else { 0 };
+    FRange {start: x1, incr: skip, front: 0, back: steps, exact: None}
+}
+
+// like range, but x2 is included if we land on it - or within rounding error of it
+fn range_inclusive(x1: f64, x2: f64, skip: f64) -> FRange {
+    if skip == 0.0 {
+        panic!("range_inclusive: increment must not be zero");
+    }
+    let steps = ((x2 - x1)/skip + 1.0e-9).floor();
+    let steps = if steps >= 0.0 { steps as usize + 1 } else { 0 };
+    FRange {start: x1, incr: skip, front: 0, back: steps, exact: None}
+}
+
+// exactly n values, evenly spaced from x1 to x2 inclusive (like NumPy's linspace).
+// x1 + (n-1)*incr need not come out as x2, so the last value is x2 itself.
+// With only one value there is no step, and we just get x1
+fn linspace(x1: f64, x2: f64, n: usize) -> FRange {
+    let (incr, exact) = if n > 1 {
+        ((x2 - x1)/((n - 1) as f64), Some((n - 1, x2)))
+    } else {
+        (0.0, None)
+    };
+    FRange {start: x1, incr: incr, front: 0, back: n, exact: exact}
+}
+
+impl FRange {
+    fn value(&self, i: usize) -> f64 {
+        match self.exact {
+            Some((index, x)) if index == i => x,
+            _ => self.start + (i as f64)*self.incr
+        }
+    }
 }
-    
 
 impl Iterator for FRange {
     type Item = f64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let res = self.val;
-        if res >= self.end {
+        if self.front >= self.back {
             None
         } else {
-            self.val += self.incr;
+            let res = self.value(self.front);
+            self.front += 1;
             Some(res)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for FRange {}
+
+impl DoubleEndedIterator for FRange {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            None
+        } else {
+            self.back -= 1;
+            Some(self.value(self.back))
+        }
+    }
 }
+
 fn main() {
 
     let sine = range(0.0,1.0,0.1).map(|x| x.sin()).collect::<Vec<f64>>();
@@ -32,6 +96,47 @@ fn main() {
 
     println!("{:?} {}",sine,sum);
 
+    let mut forward: Vec<f64> = range(0.0,1.0,0.1).collect();
+    let backward: Vec<f64> = range(0.0,1.0,0.1).rev().collect();
+    forward.reverse();
+    assert_eq!(forward, backward);
+    assert_eq!(backward.len(), 10);
+
+    let down: Vec<f64> = range(1.0,0.0,-0.1).collect();
+    assert_eq!(down.len(), 10);
+    assert_eq!(down[0], 1.0);
+    assert!(down[9] > 0.0);
+    assert_eq!(range(5.0,0.0,-1.0).collect::<Vec<_>>(), [5.0, 4.0, 3.0, 2.0, 1.0]);
+    assert_eq!(range(0.0,1.0,-0.1).count(), 0);
+
+    for r in vec![range(0.0,1.0,0.1), range(0.0,0.3,0.1), range(1.0,0.0,-0.25), range(1.0,0.0,0.1)] {
+        let n = r.len();
+        assert_eq!(n, r.collect::<Vec<_>>().len());
+    }
+    let mut r = range(0.0,1.0,0.25);
+    r.next();
+    r.next_back();
+    assert_eq!(r.len(), 2);
+
+    assert_eq!(range_inclusive(0.0,1.0,0.5).collect::<Vec<_>>(), [0.0, 0.5, 1.0]);
+    assert_eq!(range_inclusive(0.0,1.0,0.1).count(), 11);
+    assert_eq!(range_inclusive(0.0,1.0,0.3).count(), 4);
+    assert_eq!(range_inclusive(1.0,0.0,0.1).count(), 0);
+
+    assert_eq!(linspace(0.0,1.0,5).collect::<Vec<_>>(), [0.0, 0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(linspace(1.0,-1.0,3).collect::<Vec<_>>(), [1.0, 0.0, -1.0]);
+    assert_eq!(linspace(2.0,3.0,1).collect::<Vec<_>>(), [2.0]);
+    assert_eq!(linspace(2.0,3.0,0).count(), 0);
+    assert_eq!(linspace(0.0,1.0,11).len(), 11);
+    // these would miss the end by rounding error
+    assert_eq!(linspace(0.1,0.9,12).last(), Some(0.9));
+    assert_eq!(linspace(0.0,0.3,38).last(), Some(0.3));
+    assert_eq!(linspace(0.0,0.3,38).rev().next(), Some(0.3));
+    assert_eq!(linspace(0.1,0.9,12).next(), Some(0.1));
+
+    let zero_step = std::panic::catch_unwind(|| range(0.0,1.0,0.0));
+    assert!(zero_step.is_err());
+
     let tuples = [(10,"ten"),(20,"twenty"),(30,"thirty"),(40,"forty")];
     let ti = tuples.iter();
     //~ let iter = ti.filter(|t| t.0 > 20).map(|t| t.1);
@@ -1,4 +1,5 @@
 // enum1.rs
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq)]
 enum Direction {
@@ -26,6 +27,99 @@ impl Direction {
         Left => Up
         }
     }
+
+    fn dec(&self) -> Direction {
+        use Direction::*;
+        match *self {
+        Up => Left,
+        Left => Down,
+        Down => Right,
+        Right => Up
+        }
+    }
+
+    // quarter turns clockwise; negative turns go anticlockwise.
+    // Four quarters get you back where you started, so only the remainder matters
+    fn turn(&self, quarters: i32) -> Direction {
+        use Direction::*;
+        let index = match *self {
+        Up => 0,
+        Right => 1,
+        Down => 2,
+        Left => 3
+        };
+        match (index + quarters.rem_euclid(4)) % 4 {
+        0 => Up,
+        1 => Right,
+        2 => Down,
+        _ => Left
+        }
+    }
+
+    fn opposite(&self) -> Direction {
+        use Direction::*;
+        match *self {
+        Up => Down,
+        Down => Up,
+        Left => Right,
+        Right => Left
+        }
+    }
+
+    // the (dx,dy) step on a grid, where y increases downwards like screen rows
+    fn delta(&self) -> (i32,i32) {
+        use Direction::*;
+        match *self {
+        Up => (0,-1),
+        Down => (0,1),
+        Left => (-1,0),
+        Right => (1,0)
+        }
+    }
+
+    // keep turning clockwise, forever
+    fn cycle(self) -> Clockwise {
+        Clockwise{current: self}
+    }
+
+    fn from_delta(delta: (i32,i32)) -> Option<Direction> {
+        use Direction::*;
+        match delta {
+        (0,-1) => Some(Up),
+        (0,1) => Some(Down),
+        (-1,0) => Some(Left),
+        (1,0) => Some(Right),
+        _ => None
+        }
+    }
+}
+struct Clockwise {
+    current: Direction
+}
+
+impl Iterator for Clockwise {
+    type Item = Direction;
+
+    fn next(&mut self) -> Option<Direction> {
+        let next = self.current.inc();
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+// the names from as_str, in any case
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        use Direction::*;
+        match s.to_lowercase().as_str() {
+        "up" => Ok(Up),
+        "down" => Ok(Down),
+        "left" => Ok(Left),
+        "right" => Ok(Right),
+        _ => Err(format!("'{}' is not a direction",s))
+        }
+    }
 }
 
 fn main() {
@@ -36,11 +130,34 @@ fn main() {
     println!("start {}",start.as_str());
     println!("start {:?}",start);
 
-    let mut d = start;
-    for _ in 0..8 {
+    for d in start.cycle().take(8) {
         println!("d {:?}",d);
-        d = d.inc();
     }
+    let clockwise: Vec<_> = Direction::Up.cycle().take(4).collect();
+    assert_eq!(clockwise, [Direction::Up, Direction::Right, Direction::Down, Direction::Left]);
+    assert_eq!(Direction::Up.cycle().nth(4), Some(Direction::Up));
 
-    
+    for d in &[Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+        assert_eq!(d.inc().dec(), *d);
+        assert_eq!(d.dec().inc(), *d);
+        assert_eq!(d.opposite().opposite(), *d);
+        assert_eq!(d.inc().inc(), d.opposite());
+        assert_eq!(d.as_str().parse::<Direction>().unwrap(), *d);
+        assert_eq!(Direction::from_delta(d.delta()).as_ref(), Some(d));
+        assert_eq!(d.turn(0), *d);
+        assert_eq!(d.turn(1), d.inc());
+        assert_eq!(d.turn(-1), d.dec());
+        assert_eq!(d.turn(2), d.opposite());
+        assert_eq!(d.turn(-2), d.opposite());
+        assert_eq!(d.turn(5), d.inc());
+        assert_eq!(d.turn(-7), d.inc());
+        assert_eq!(d.turn(4000), *d);
+        assert_eq!(d.turn(i32::MAX), d.dec());
+        assert_eq!(d.turn(i32::MIN), *d);
+    }
+    assert_eq!(Direction::Up.delta(), (0,-1));
+    assert_eq!(Direction::Right.delta(), (1,0));
+    assert_eq!(Direction::from_delta((1,1)), None);
+    assert_eq!("left".parse(), Ok(Direction::Left));
+    assert_eq!("sideways".parse::<Direction>(), Err("'sideways' is not a direction".to_string()));
 }
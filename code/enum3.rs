@@ -7,6 +7,20 @@ enum Value {
     Bool(bool)
 }
 
+use std::fmt;
+
+// like the sexpr Value, but without the trailing spaces
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Value::*;
+        match *self {
+        Number(n) => write!(f,"{}",n),
+        Str(ref s) => write!(f,"{}",s),
+        Bool(b) => write!(f,"{}",b)
+        }
+    }
+}
+
 fn dump(v: &Value) {
     use Value::*;
     match *v {
@@ -30,6 +44,28 @@ impl Value {
         _ => None
         }
     }
+
+    fn to_bool(self) -> Option<bool> {
+        match self {
+        Value::Bool(b) => Some(b),
+        _ => None
+        }
+    }
+
+    // these borrow, so the value can still be used afterwards
+    fn as_number(&self) -> Option<f64> {
+        match *self {
+        Value::Number(n) => Some(n),
+        _ => None
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match *self {
+        Value::Str(ref s) => Some(s),
+        _ => None
+        }
+    }
 }
 
 fn main() {
@@ -42,5 +78,20 @@ fn main() {
 
     dump(&s);
 
+    assert_eq!(n.as_number(), Some(2.3));
+    assert_eq!(n.as_number(), Some(2.3));
+    assert_eq!(s.as_str(), Some("hello"));
+    assert_eq!(s.as_str(), Some("hello"));
+    assert_eq!(b.as_str(), None);
+
+    println!("n {} s {} b {}",n,s,b);
+    assert_eq!(n.to_string(), "2.3");
+    assert_eq!(s.to_string(), "hello");
+    assert_eq!(b.to_string(), "true");
+
     println!("s? {:?}",s.to_str());
+
+    assert_eq!(b.to_bool(), Some(true));
+    assert_eq!(n.to_number(), Some(2.3));
+    assert_eq!(Number(1.0).to_bool(), None);
 }
@@ -1,29 +1,54 @@
 // error2.rs
 use std::error::Error;
 use std::fmt;
+use std::fs::File;
+use std::io;
+use std::iter;
+use std::io::prelude::*;
 
 #[derive(Debug)]
 struct MyError {
     details: String,
+    code: Option<i32>,
     original_error: Option<Box<Error>>
 }
 
 impl MyError {
     fn new(msg: &str) -> MyError {
-        MyError{details: msg.to_string(), original_error: None}
+        MyError{details: msg.to_string(), code: None, original_error: None}
     }
 
     fn from<E: Error + 'static> (e: E) -> MyError {
         MyError{
-            details: e.description().to_string(),
+            details: e.to_string(),
+            code: None,
             original_error: Some(Box::new(e))
         }
     }
+
+    fn with_code(mut self, code: i32) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    // this error, followed by its cause, and its cause's cause...
+    fn chain(&self) -> impl Iterator<Item=&Error> {
+        let mut current: Option<&Error> = Some(self);
+        iter::from_fn(move || {
+            let err = current?;
+            current = err.cause();
+            Some(err)
+        })
+    }
 }
 
 impl fmt::Display for MyError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}",self.details)
+        write!(f,"{}",self.details)?;
+        if let Some(code) = self.code {
+            write!(f," (code {})",code)?;
+        }
+        Ok(())
     }
 }
 
@@ -35,7 +60,7 @@ impl Error for MyError {
 
     fn cause(&self) -> Option<&Error> {
         match self.original_error {
-            Some(ref err) => Some(&*err),
+            Some(ref err) => Some(&**err),
             None => None
         }
     }
@@ -44,16 +69,43 @@ impl Error for MyError {
 
 impl From<std::num::ParseFloatError> for MyError {
     fn from(err: std::num::ParseFloatError) -> Self {
-        MyError::new(err.description())
+        MyError::new(&err.to_string())
+    }
+}
+
+// early return with a MyError, formatted like println!
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(MyError::new(&format!($($arg)*)))
+    }
+}
+
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)*) => {
+        if ! $cond {
+            bail!($($arg)*);
+        }
+    }
+}
+
+// keep the io error around, so cause() can find it
+impl From<io::Error> for MyError {
+    fn from(err: io::Error) -> Self {
+        MyError::from(err)
     }
 }
 
 fn raises_my_error(yes: bool) -> Result<(),MyError> {
-    if yes {
-        Err(MyError::new("borked"))
-    } else {
-        Ok(())
+    ensure!(! yes, "borked");
+    Ok(())
+}
+
+fn check_percent(x: f64) -> Result<(),MyError> {
+    if x < 0.0 {
+        bail!("negative value {}",x);
     }
+    ensure!(x <= 100.0, "{} is more than 100%",x);
+    Ok(())
 }
 
 fn parse_f64(s: &str, yes: bool) -> Result<f64,MyError> {
@@ -62,8 +114,33 @@ fn parse_f64(s: &str, yes: bool) -> Result<f64,MyError> {
     Ok(x)
 }
 
+fn read_f64(file: &str) -> Result<f64,MyError> {
+    let mut text = String::new();
+    File::open(file)?.read_to_string(&mut text)?;
+    let x: f64 = text.trim().parse()?;
+    Ok(x)
+}
+
 fn main() {
     println!(" {:?}",parse_f64("42",false));
     println!(" {:?}",parse_f64("42",true));
     println!(" {:?}",parse_f64("?42",false));
+
+    let err = read_f64("no-such-file.txt").unwrap_err();
+    println!(" {} caused by {:?}",err,err.cause());
+    assert!(err.cause().is_some());
+
+    let nested = MyError::from("?42".parse::<f64>().unwrap_err());
+    for e in nested.chain() {
+        println!(" -> {}",e);
+    }
+    assert_eq!(nested.chain().count(), 2);
+
+    let coded = MyError::new("not found").with_code(404);
+    assert_eq!(coded.code, Some(404));
+    assert_eq!(coded.to_string(), "not found (code 404)");
+
+    assert!(check_percent(50.0).is_ok());
+    assert_eq!(check_percent(-1.0).unwrap_err().to_string(), "negative value -1");
+    assert_eq!(check_percent(120.0).unwrap_err().to_string(), "120 is more than 100%");
 }
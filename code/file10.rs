@@ -1,7 +1,35 @@
 // file10.rs
 use std::env;
 use std::path::Path;
-use std::os::unix::fs::PermissionsExt;
+use std::fs::Metadata;
+
+// only Unix has the octal mode bits; Windows just knows about read-only
+#[cfg(unix)]
+fn print_permissions(data: &Metadata) {
+    use std::os::unix::fs::PermissionsExt;
+    println!("perm {:o}",data.permissions().mode());
+}
+
+#[cfg(windows)]
+fn print_permissions(data: &Metadata) {
+    println!("read-only {}",data.permissions().readonly());
+}
+
+// 1536 is "1.5 KiB"; anything under 1024 is just bytes
+fn human_size(bytes: u64) -> String {
+    let units = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} bytes",bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    // compare the size as it will be printed, so 1048575 bytes is '1.0 MiB', not '1024.0 KiB'
+    while (size * 10.0).round() >= 10240.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}",size,units[unit])
+}
 
 fn main() {
     let file = env::args().skip(1).next().unwrap_or("file10.rs".to_string());
@@ -9,11 +37,19 @@ fn main() {
     match path.metadata() {
         Ok(data) => {
             println!("type {:?}",data.file_type());
-            println!("len {}",data.len());
-            println!("perm {:o}",data.permissions().mode());
+            println!("len {}",human_size(data.len()));
+            print_permissions(&data);
             
             println!("modified {:?}",data.modified());
         },
         Err(e) => println!("error {:?}",e)
     }
+
+    assert_eq!(human_size(1023), "1023 bytes");
+    assert_eq!(human_size(1024), "1.0 KiB");
+    assert_eq!(human_size(1536), "1.5 KiB");
+    assert_eq!(human_size(1048576), "1.0 MiB");
+    assert_eq!(human_size(1048575), "1.0 MiB");
+    assert_eq!(human_size(1048000), "1023.4 KiB");
+    assert_eq!(human_size(5 * 1024 * 1024 * 1024 * 1024), "5120.0 GiB");
 }
@@ -2,20 +2,52 @@
 use std::env;
 use std::fs;
 use std::io;
+use std::path::{Path,PathBuf};
+use std::fs::Metadata;
 
-fn dump_dir(dir: &str) -> io::Result<()> {
+// files in dir with the given extension (in any case) that are at least min_size bytes
+fn find_files(dir: &str, ext: &str, min_size: u64) -> io::Result<Vec<PathBuf>> {
+    let mut res = Vec::new();
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let data = entry.metadata()?;
         let path = entry.path();
-        if data.is_file() {
+        if data.is_file() && data.len() >= min_size {
             if let Some(ex) = path.extension() {
-                if ex == "rs" && data.len() > 1024 {
-                    println!("{} length {}",path.display(),data.len());
+                if ex.to_string_lossy().to_lowercase() == ext.to_lowercase() {
+                    res.push(path);
                 }
             }
         }
     }
+    Ok(res)
+}
+
+fn dump_dir(dir: &str) -> io::Result<()> {
+    // bigger than 1K, as always: min_size is 'at least', so one more than 1024
+    for path in find_files(dir, "rs", 1025)? {
+        println!("{} length {}",path.display(),path.metadata()?.len());
+    }
+    Ok(())
+}
+
+// call f for every file under dir, recursively. The metadata is for the entry itself,
+// so symlinks are reported but not followed - a symlink can't lead us in circles
+fn walk_dir(dir: &str, f: &mut dyn FnMut(&Path, &Metadata)) -> io::Result<()> {
+    walk_path(Path::new(dir), f)
+}
+
+fn walk_path(dir: &Path, f: &mut dyn FnMut(&Path, &Metadata)) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let data = fs::symlink_metadata(entry.path())?;
+        let path = entry.path();
+        if data.is_dir() {
+            walk_path(&path, f)?;
+        } else {
+            f(&path, &data);
+        }
+    }
     Ok(())
 }
 
@@ -23,4 +55,44 @@ fn main() {
     let dir = env::args().skip(1).next().unwrap_or(".".to_string());
 
     dump_dir(&dir).expect("could not dump dir");
+
+    // make a little tree to walk
+    let tmp = env::temp_dir().join("file11-walk");
+    let _ = fs::remove_dir_all(&tmp);
+    fs::create_dir_all(tmp.join("a").join("b")).expect("can't create temp dirs");
+    for file in &["one.txt", "a/two.txt", "a/b/three.txt"] {
+        fs::write(tmp.join(file), "hello").expect("can't write temp file");
+    }
+    let mut found = Vec::new();
+    walk_dir(tmp.to_str().unwrap(), &mut |path, data| {
+        found.push((path.file_name().unwrap().to_string_lossy().to_string(), data.len()));
+    }).expect("could not walk dir");
+    found.sort();
+    assert_eq!(found, [("one.txt".to_string(),5), ("three.txt".to_string(),5), ("two.txt".to_string(),5)]);
+
+    // a symlink to a directory is reported, but we don't go inside it
+    #[cfg(unix)]
+    {
+        let link = tmp.join("link");
+        std::os::unix::fs::symlink(tmp.join("a"), &link).expect("can't make symlink");
+        let mut found = Vec::new();
+        walk_dir(tmp.to_str().unwrap(), &mut |path, _| found.push(path.to_path_buf())).expect("could not walk dir");
+        found.sort();
+        assert_eq!(found, [tmp.join("a/b/three.txt"), tmp.join("a/two.txt"), link.clone(), tmp.join("one.txt")]);
+        fs::remove_file(&link).expect("can't remove symlink");
+    }
+
+    fs::write(tmp.join("big.RS"), vec![b'x'; 2000]).expect("can't write temp file");
+    fs::write(tmp.join("big.txt"), vec![b'x'; 2000]).expect("can't write temp file");
+    fs::write(tmp.join("small.rs"), "fn main() {}").expect("can't write temp file");
+    fs::write(tmp.join("exact.rs"), vec![b'x'; 1024]).expect("can't write temp file");
+    let over = find_files(tmp.to_str().unwrap(), "rs", 1025).expect("could not find files");
+    assert_eq!(over, [tmp.join("big.RS")]);
+    fs::remove_file(tmp.join("exact.rs")).expect("can't remove temp file");
+    let mut big = find_files(tmp.to_str().unwrap(), "rs", 1024).expect("could not find files");
+    assert_eq!(big, [tmp.join("big.RS")]);
+    big = find_files(tmp.to_str().unwrap(), "rs", 0).expect("could not find files");
+    big.sort();
+    assert_eq!(big, [tmp.join("big.RS"), tmp.join("small.rs")]);
+    fs::remove_dir_all(&tmp).expect("can't clean up");
 }
@@ -1,22 +1,81 @@
 // file2.rs
 use std::env;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
 use std::io;
 
-fn read_to_string(filename: &str) -> io::Result<String> {
+// the message says what went wrong in plain words; the io::Error is kept as the cause
+#[derive(Debug)]
+struct MyError {
+    details: String,
+    original_error: io::Error
+}
+
+impl MyError {
+    fn from_io(filename: &str, e: io::Error) -> MyError {
+        let details = match e.kind() {
+            io::ErrorKind::NotFound => format!("no such file: {}",filename),
+            io::ErrorKind::PermissionDenied => format!("cannot read {}: permission denied",filename),
+            _ => format!("cannot read {}: {}",filename,e)
+        };
+        MyError{details: details, original_error: e}
+    }
+}
+
+impl fmt::Display for MyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.details)
+    }
+}
+
+impl Error for MyError {
+    fn description(&self) -> &str {
+        &self.details
+    }
+
+    fn source(&self) -> Option<&(Error + 'static)> {
+        Some(&self.original_error)
+    }
+}
+
+fn read_to_string(filename: &str) -> Result<String,MyError> {
     let mut file = match File::open(&filename) {
         Ok(f) => f,
-        Err(e) => return Err(e)
+        Err(e) => return Err(MyError::from_io(filename, e))
     };
     let mut text = String::new();
     match file.read_to_string(&mut text) {
         Ok(_) => Ok(text),
-        Err(e) => Err(e)
+        Err(e) => Err(MyError::from_io(filename, e))
     }
 }
 
+// root can read anything, so only check when the file really is unreadable
+#[cfg(unix)]
+fn check_permission_denied() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = env::temp_dir().join("file2-locked.txt");
+    fs::write(&path, "secret").expect("can't write temp file");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).expect("can't set permissions");
+    let name = path.to_str().unwrap();
+    if File::open(&path).is_err() {
+        let err = read_to_string(name).unwrap_err();
+        assert_eq!(err.to_string(), format!("cannot read {}: permission denied",name));
+    }
+    fs::remove_file(&path).expect("can't remove temp file");
+}
+
 fn main() {
+    let err = read_to_string("no-such-file.txt").unwrap_err();
+    assert_eq!(err.to_string(), "no such file: no-such-file.txt");
+    assert_eq!(err.original_error.kind(), io::ErrorKind::NotFound);
+    #[cfg(unix)]
+    check_permission_denied();
+
     let file = env::args().nth(1).expect("please supply a filename");
     
     let text = read_to_string(&file).expect("bad file man!");
@@ -1,20 +1,85 @@
 // file3.rs
+extern crate flate2;
+
 use std::env;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read,Write,BufRead};
 use std::io;
+use flate2::read::GzDecoder;
+
+// every gzip file starts with these two bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// gzipped files are decompressed on the fly. We peek at the start
+// of the buffered file, so nothing is consumed before we decide
+fn open_text(filename: &str) -> io::Result<Box<Read>> {
+    let mut file = io::BufReader::new(File::open(&filename)?);
+    let gzipped = file.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if gzipped {
+        Ok(Box::new(GzDecoder::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
 
 fn read_to_string(filename: &str) -> io::Result<String> {
-    let mut file = File::open(&filename)?;
     let mut text = String::new();
-    file.read_to_string(&mut text)?;
+    open_text(filename)?.read_to_string(&mut text)?;
     Ok(text)
 }
 
+// read at most max bytes; a bigger file is an error rather than a surprise.
+// We ask for one byte more than allowed, to find out if there is more.
+// The cap may fall in the middle of a UTF-8 character, so check the size
+// on the raw bytes before converting them. For a gzip file the cap applies
+// to the decompressed text, just as read_to_string sees it
+fn read_to_string_capped(filename: &str, max: usize) -> io::Result<String> {
+    let file = open_text(filename)?;
+    let mut bytes = Vec::new();
+    file.take(max as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() > max {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+            format!("{} is bigger than {} bytes",filename,max)));
+    }
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// the text just fits the cap, and one byte less does not
+fn check_capped(file: &str, text: &str) {
+    let small = read_to_string_capped(file, text.len()).expect("file should fit");
+    assert_eq!(small, text);
+    if text.len() > 0 {
+        let err = read_to_string_capped(file, text.len() - 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
 fn main() {
     let file = env::args().nth(1).expect("please supply a filename");
     
     let text = read_to_string(&file).expect("bad file man!");
 
     println!("file had {} bytes",text.len());
+
+    check_capped(&file, &text);
+
+    // the cap lands inside the two bytes of 'é'
+    let accent = env::temp_dir().join("file3-accent.txt");
+    std::fs::write(&accent, "café").expect("can't write temp file");
+    let err = read_to_string_capped(accent.to_str().unwrap(), 4).unwrap_err();
+    assert!(err.to_string().contains("bigger than 4 bytes"));
+    assert_eq!(read_to_string_capped(accent.to_str().unwrap(), 5).unwrap(), "café");
+    std::fs::remove_file(&accent).expect("can't remove temp file");
+
+    let gz = env::temp_dir().join("file3-test.txt.gz");
+    {
+        let out = File::create(&gz).expect("can't create temp file");
+        let mut enc = flate2::write::GzEncoder::new(out, flate2::Compression::default());
+        enc.write_all(b"hello\ncompressed world\n").expect("can't compress");
+        enc.finish().expect("can't finish compressing");
+    }
+    let gz_text = read_to_string(gz.to_str().unwrap()).expect("can't read gzip file");
+    assert_eq!(gz_text, "hello\ncompressed world\n");
+    check_capped(gz.to_str().unwrap(), &gz_text);
+    std::fs::remove_file(&gz).expect("can't remove temp file");
 }
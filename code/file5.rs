@@ -2,30 +2,170 @@
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
+use std::io::Cursor;
 
-struct Lines<R> {
-    reader: io::BufReader<R>,
-    buf: String
+// remove a trailing "\n" or "\r\n", but leave any other whitespace alone
+fn strip_line_ending(s: &str) -> &str {
+    let mut line = s;
+    if line.ends_with('\n') {
+        line = &line[..line.len()-1];
+        if line.ends_with('\r') {
+            line = &line[..line.len()-1];
+        }
+    }
+    line
+}
+
+struct Lines<B> {
+    reader: B,
+    buf: String,
+    skip_blank: bool,
+    line_no: usize
+}
+
+impl <R: Read> Lines<io::BufReader<R>> {
+    fn new(r: R) -> Lines<io::BufReader<R>> {
+        Lines::from_buf(io::BufReader::new(r))
+    }
 }
 
-impl <R: Read> Lines<R> {
-    fn new(r: R) -> Lines<R> {
-        Lines{reader: io::BufReader::new(r), buf: String::new()}
+impl <B: BufRead> Lines<B> {
+    // use a reader that is already buffered, rather than buffering it twice
+    fn from_buf(b: B) -> Lines<B> {
+        Lines{reader: b, buf: String::new(), skip_blank: false, line_no: 0}
+    }
+
+    // don't return lines that are empty or only whitespace
+    fn skip_blank(mut self) -> Lines<B> {
+        self.skip_blank = true;
+        self
+    }
+
+    // 1-based number of the line last returned by next, counting skipped lines
+    fn line_no(&self) -> usize {
+        self.line_no
+    }
+
+    // read the next line we want into the buffer
+    fn advance(&mut self) -> Option<io::Result<()>> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.line_no += 1;
+                    if ! (self.skip_blank && self.buf.trim().is_empty()) {
+                        return Some(Ok(()));
+                    }
+                },
+                Err(e) => return Some(Err(e))
+            }
+        }
     }
 
     fn next<'a>(&'a mut self) -> Option<io::Result<&'a str>>{
-        self.buf.clear();
-        match self.reader.read_line(&mut self.buf) {
-            Ok(nbytes) => if nbytes == 0 {
-                None
-            } else {
-                let line = self.buf.trim_right();
-                Some(Ok(line))
+        match self.advance()? {
+            Ok(()) => Some(Ok(strip_line_ending(&self.buf))),
+            Err(e) => Some(Err(e))
+        }
+    }
+
+    // like next, but each line comes with its 1-based line number.
+    // Skipped blank lines still count
+    fn enumerate_lines<'a>(&'a mut self) -> Option<io::Result<(usize,&'a str)>>{
+        match self.advance()? {
+            Ok(()) => Some(Ok((self.line_no, strip_line_ending(&self.buf)))),
+            Err(e) => Some(Err(e))
+        }
+    }
+
+    // owned lines, with their line numbers
+    fn numbered(self) -> NumberedLines<B> {
+        NumberedLines{lines: self}
+    }
+
+}
+
+// Lines can't be an Iterator itself, because each line borrows the buffer.
+// But we can pay for an allocation per line and get owned strings instead.
+struct OwnedLines<B> {
+    lines: Lines<B>
+}
+
+impl <B: BufRead> Iterator for OwnedLines<B> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(|res| res.map(|line| line.to_string()))
+    }
+}
+
+struct NumberedLines<B> {
+    lines: Lines<B>
+}
+
+impl <B: BufRead> Iterator for NumberedLines<B> {
+    type Item = io::Result<(usize,String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.enumerate_lines().map(|res| res.map(|(n,line)| (n,line.to_string())))
+    }
+}
+
+impl <B: BufRead> IntoIterator for Lines<B> {
+    type Item = io::Result<String>;
+    type IntoIter = OwnedLines<B>;
+
+    fn into_iter(self) -> OwnedLines<B> {
+        OwnedLines{lines: self}
+    }
+}
+
+// Like Lines, but split on any byte, e.g. 0 for the output of 'find -print0'.
+// The records are bytes, since they need not be valid UTF-8
+struct Records<R> {
+    reader: io::BufReader<R>,
+    delim: u8
+}
+
+impl <R: Read> Records<R> {
+    fn new(r: R) -> Records<R> {
+        Records{reader: io::BufReader::new(r), delim: b'\n'}
+    }
+
+    fn delimiter(mut self, delim: u8) -> Records<R> {
+        self.delim = delim;
+        self
+    }
+}
+
+impl <R: Read> Iterator for Records<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = Vec::new();
+        match self.reader.read_until(self.delim, &mut buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                // the last record may not have a delimiter
+                if buf.last() == Some(&self.delim) {
+                    buf.pop();
+                }
+                Some(Ok(buf))
             },
             Err(e) => Some(Err(e))
         }
     }
+}
 
+// only one line is held in memory at a time.
+// A last line without a newline still counts
+fn count_lines<R: Read>(r: R) -> io::Result<usize> {
+    let mut lines = Lines::new(r);
+    while let Some(line) = lines.next() {
+        line?;
+    }
+    Ok(lines.line_no())
 }
 
 fn read_all_lines(filename: &str) -> io::Result<()> {
@@ -43,4 +183,55 @@ fn read_all_lines(filename: &str) -> io::Result<()> {
 
 fn main() {
     read_all_lines("file4.rs").expect("bad file man!");
+
+    let text = Cursor::new("one\ntwo\nthree\n");
+    let mut lines = Vec::new();
+    for line in Lines::new(text) {
+        lines.push(line.expect("can't fail reading memory"));
+    }
+    assert_eq!(lines, ["one", "two", "three"]);
+
+    let mut crlf = Lines::new(Cursor::new("a  \r\nb"));
+    assert_eq!(crlf.next().unwrap().unwrap(), "a  ");
+    assert_eq!(crlf.next().unwrap().unwrap(), "b");
+
+    let mut sparse = Lines::new(Cursor::new("a\n\n\nb\n")).skip_blank();
+    assert_eq!(sparse.next().unwrap().unwrap(), "a");
+    assert_eq!(sparse.line_no(), 1);
+    assert_eq!(sparse.next().unwrap().unwrap(), "b");
+    assert_eq!(sparse.line_no(), 4);
+    assert!(sparse.next().is_none());
+
+    let mut numbered = Lines::new(Cursor::new("a\n\n  \nb\nc")).skip_blank();
+    assert_eq!(numbered.enumerate_lines().unwrap().unwrap(), (1, "a"));
+    assert_eq!(numbered.enumerate_lines().unwrap().unwrap(), (4, "b"));
+    assert_eq!(numbered.enumerate_lines().unwrap().unwrap(), (5, "c"));
+    assert!(numbered.enumerate_lines().is_none());
+    let owned: Vec<_> = Lines::new(Cursor::new("x\n\ny\n")).skip_blank().numbered()
+        .map(|r| r.expect("can't fail reading memory"))
+        .collect();
+    assert_eq!(owned, [(1, "x".to_string()), (3, "y".to_string())]);
+
+    assert_eq!(count_lines(Cursor::new("one\ntwo\n")).unwrap(), 2);
+    assert_eq!(count_lines(Cursor::new("one\ntwo")).unwrap(), 2);
+    assert_eq!(count_lines(Cursor::new("\n\n")).unwrap(), 2);
+    assert_eq!(count_lines(Cursor::new("")).unwrap(), 0);
+
+    // locked stdin is already buffered
+    let stdin = io::stdin();
+    let _locked = Lines::from_buf(stdin.lock());
+    let mut buffered = Lines::from_buf(io::BufReader::new(Cursor::new("first\nsecond")));
+    assert_eq!(buffered.next().unwrap().unwrap(), "first");
+    assert_eq!(buffered.next().unwrap().unwrap(), "second");
+    assert!(buffered.next().is_none());
+    // a Cursor is already a BufRead, so it can be used as it is
+    let direct: Vec<_> = Lines::from_buf(Cursor::new("a\nb\n")).into_iter()
+        .map(|l| l.unwrap())
+        .collect();
+    assert_eq!(direct, ["a", "b"]);
+
+    let records: Vec<_> = Records::new(Cursor::new(b"a\0b\0c".to_vec())).delimiter(0)
+        .map(|r| r.expect("can't fail reading memory"))
+        .collect();
+    assert_eq!(records, [b"a", b"b", b"c"]);
 }
@@ -0,0 +1,33 @@
+// frame.rs
+// Lines are no good for data that may contain newlines. So each frame
+// is sent as a 4-byte big-endian length, followed by that many bytes.
+use std::io;
+use std::io::prelude::*;
+
+// The length comes from the other side, and we must not allocate
+// whatever it asks for. So there is a limit on the size of a frame
+pub const MAX_FRAME: usize = 16 * 1024 * 1024;
+
+pub fn write_frame<W: Write>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    if data.len() > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too large"));
+    }
+    let len = data.len() as u32;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(data)?;
+    w.flush()
+}
+
+// fails with ErrorKind::UnexpectedEof if the stream ends, even between frames
+pub fn read_frame<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+            format!("frame of {} bytes is bigger than {}",len,MAX_FRAME)));
+    }
+    let mut data = vec![0u8; len];
+    r.read_exact(&mut data)?;
+    Ok(data)
+}
@@ -8,7 +8,8 @@ fn abs(x: f64) -> f64 {
     }
 }
 
-fn clamp(x: f64, x1: f64, x2: f64) -> f64 {
+// works for anything that can be compared: integers, floats, strings...
+fn clamp<T: PartialOrd>(x: T, x1: T, x2: T) -> T {
     if x < x1 {
         x1
     } else if x > x2 {
@@ -18,20 +19,29 @@ fn clamp(x: f64, x1: f64, x2: f64) -> f64 {
     }
 }
 
-fn fact(n: u64) -> u64 {
-    if n == 0 {
-        1
-    } else {
-        n*fact(n-1)
+// factorials grow fast: 21! is too big for a u64, so we get None
+fn fact(n: u64) -> Option<u64> {
+    let mut res: u64 = 1;
+    for i in 2..=n {
+        res = res.checked_mul(i)?;
     }
+    Some(res)
 }
 
 fn main() {
     let res1 = abs(-10.0);
     let res2 = clamp(1.5,0.0,1.0);
-    let res3 = fact(4);
+    let res3 = fact(4).unwrap();
     println!("res1 is {}",res1);
     println!("res2 is {}",res2);
     println!("res3 is {}",res3);
-    
+
+    assert_eq!(clamp(5, 0, 3), 3);
+    assert_eq!(clamp(-5, 0, 3), 0);
+    assert_eq!(clamp(2, 0, 3), 2);
+    assert_eq!(clamp(0.5, 0.0, 1.0), 0.5);
+    assert_eq!(clamp("m", "a", "k"), "k");
+    assert_eq!(fact(0), Some(1));
+    assert_eq!(fact(20), Some(2432902008176640000));
+    assert_eq!(fact(21), None);
 }
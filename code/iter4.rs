@@ -1,7 +1,15 @@
 // iter4.rs
 
+// the names whose number satisfies the predicate
+fn names_where<'a>(tuples: &'a [(&'a str, i32)], pred: impl Fn(i32) -> bool) -> Vec<&'a str> {
+    tuples.iter()
+        .filter(|&&(_,num)| pred(num))
+        .map(|&(name,_)| name)
+        .collect()
+}
+
 fn main() {
-    let mut vec = vec!["one".to_string(),"two".to_string()];
+    let vec = vec!["one".to_string(),"two".to_string()];
 
     for n in vec.iter().map(|x: &String| x.len()) {
         println!("{:?}",n);
@@ -20,10 +28,14 @@ fn main() {
     }
 
     let tuples = [("hello",1),("dolly",2)];
-    for s in tuples.iter().filter(|(&name,&num)| name == "one") {
+    for s in tuples.iter().filter(|&&(name,_)| name == "dolly") {
         println!("{:?}",s);
     }
 
+    assert_eq!(names_where(&tuples, |n| n > 1), ["dolly"]);
+    assert_eq!(names_where(&tuples, |n| n > 0), ["hello","dolly"]);
+    assert!(names_where(&tuples, |n| n > 2).is_empty());
+
     //~ for s in &vec { println!("{:?}",s); }
     //~ for s in &mut vec { println!("{:?}",s); }
     //~ for s in vec {  println!("{:?}",s);  }
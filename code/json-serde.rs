@@ -0,0 +1,73 @@
+// json-serde.rs
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Person {
+    name: String,
+    // missing fields get their Default value: 0 and an empty vector
+    #[serde(default)]
+    age: u8,
+    address: Address,
+    #[serde(default, deserialize_with = "valid_phones")]
+    phones: Vec<String>,
+    // an Option field may just be left out
+    email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+// reject the whole document if any phone number isn't at least seven digits
+fn valid_phones<'de, D>(d: D) -> Result<Vec<String>, D::Error>
+where D: Deserializer<'de> {
+    let phones = Vec::<String>::deserialize(d)?;
+    for phone in &phones {
+        if phone.len() < 7 || ! phone.chars().all(|c| c.is_digit(10)) {
+            return Err(D::Error::custom(format!("bad phone number '{}'", phone)));
+        }
+    }
+    Ok(phones)
+}
+
+fn main() {
+    let data = r#" {
+     "name": "John Doe", "age": 43,
+     "address": {"street": "main", "city":"Downtown"},
+     "phones":["27726550023"]
+    } "#;
+    let p: Person = serde_json::from_str(data).expect("deserialize error");
+    println!("Please call {} at the number {}", p.name, p.phones[0]);
+
+    println!("{:#?}",p);
+
+    let bad = data.replace("27726550023", "555-1234");
+    let err = serde_json::from_str::<Person>(&bad).unwrap_err();
+    println!("error {}",err);
+    assert!(err.to_string().starts_with("bad phone number '555-1234'"));
+    assert!(serde_json::from_str::<Person>(&data.replace("27726550023", "123")).is_err());
+
+    let partial = r#" {
+     "name": "Jane Doe",
+     "address": {"street": "high", "city":"Uptown"},
+     "email": "jane@example.com"
+    } "#;
+    let p: Person = serde_json::from_str(partial).expect("deserialize error");
+    assert_eq!(p.age, 0);
+    assert!(p.phones.is_empty());
+    assert_eq!(p.email, Some("jane@example.com".to_string()));
+
+    // and back again - even with no phones the round trip gives the same Person
+    let text = serde_json::to_string_pretty(&p).expect("serialize error");
+    println!("{}",text);
+    let q: Person = serde_json::from_str(&text).expect("deserialize error");
+    assert_eq!(p, q);
+}
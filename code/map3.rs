@@ -2,27 +2,109 @@
 use std::fs::File;
 use std::io::prelude::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap,HashSet};
+
+struct Config {
+    lowercase: bool,
+    min_length: usize,
+}
+
+// the usual setting: ignore case, keep every word
+impl Default for Config {
+    fn default() -> Config {
+        Config { lowercase: true, min_length: 1 }
+    }
+}
+
+// words are runs of letters; shorter words are skipped
+fn words(text: &str, config: &Config) -> Vec<String> {
+    text.split(|c: char| ! c.is_alphabetic())
+        .filter(|s| ! s.is_empty() && s.chars().count() >= config.min_length)
+        .map(|s| if config.lowercase { s.to_lowercase() } else { s.to_string() })
+        .collect()
+}
+
+fn count_words(text: &str, config: &Config, stopwords: &HashSet<String>) -> HashMap<String,usize> {
+    let mut map = HashMap::new();
+    for word in words(text, config) {
+        if ! stopwords.contains(&word) {
+            *map.entry(word).or_insert(0) += 1;
+        }
+    }
+    map
+}
+
+fn word_frequencies(text: &str, config: &Config) -> HashMap<String,usize> {
+    count_words(text, config, &HashSet::new())
+}
+
+// most frequent first. HashMap order is random, so break ties alphabetically
+fn top_n(freqs: &HashMap<String,usize>, n: usize) -> Vec<(String,usize)> {
+    let mut entries: Vec<_> = freqs.iter().map(|(w,&c)| (w.clone(),c)).collect();
+    entries.sort_by(|a,b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(n);
+    entries
+}
+
+// one 'word count' line for each of the top entries
+fn format_report(freqs: &HashMap<String,usize>, top: usize) -> String {
+    let mut report = String::new();
+    for (word,count) in top_n(freqs, top) {
+        report += &format!("{} {}\n", word, count);
+    }
+    report
+}
+
+// how often each pair of adjacent words occurs
+fn bigram_frequencies(text: &str) -> HashMap<(String,String),usize> {
+    let mut map = HashMap::new();
+    for pair in words(text, &Config::default()).windows(2) {
+        *map.entry((pair[0].clone(),pair[1].clone())).or_insert(0) += 1;
+    }
+    map
+}
 
 fn main() {
     let mut f = File::  open("sherlock.txt").expect("can't open sherlock.txt");
     let mut text = String::new();
     f.read_to_string(&mut text).expect("can't read the file");
-    let mut map = HashMap::new();
-
-    for s in text.split(|c: char| ! c.is_alphabetic()) {
-        let word = s.to_lowercase();
-        let mut entry = map.entry(word).or_insert(0);
-        *entry += 1;
-    }
+    let map = word_frequencies(&text, &Config::default());
 
     println!("total words {}",map.len());
 
-    let mut entries: Vec<_> = map.into_iter().collect();
-    entries.sort_by(|a,b| b.1.cmp(&a.1));
-    for e in entries.iter().take(20) {
-        println!("{} {}", e.0, e.1);
-    }
+    print!("{}", format_report(&map, 20));
+
+    let freqs = word_frequencies("the cat sat on the mat", &Config::default());
+    assert_eq!(freqs["the"], 2);
+    assert_eq!(freqs["cat"], 1);
+    assert_eq!(top_n(&freqs, 3), [
+        ("the".to_string(),2), ("cat".to_string(),1), ("mat".to_string(),1)
+    ]);
+
+    assert_eq!(format_report(&freqs, 2), "the 2\ncat 1\n");
+    assert_eq!(format_report(&freqs, 0), "");
+
+    let bigrams = bigram_frequencies("a b a b");
+    assert_eq!(bigrams[&("a".to_string(),"b".to_string())], 2);
+    assert_eq!(bigrams[&("b".to_string(),"a".to_string())], 1);
+    assert_eq!(bigrams.len(), 2);
+
+    let stopwords = ["the","and","a"].iter().map(|s| s.to_string()).collect();
+    let all = count_words("The cat and the hat", &Config::default(), &HashSet::new());
+    assert_eq!(all["the"], 2);
+    assert_eq!(all.len(), 4);
+    let some = count_words("The cat and the hat", &Config::default(), &stopwords);
+    assert_eq!(some.get("the"), None);
+    assert_eq!(some.len(), 2);
 
+    let text = "Foo foo FOO is a word";
+    let folded = word_frequencies(text, &Config::default());
+    assert_eq!(folded["foo"], 3);
+    assert_eq!(folded.len(), 4);
+    let cased = word_frequencies(text, &Config { lowercase: false, min_length: 3 });
+    assert_eq!(cased["Foo"], 1);
+    assert_eq!(cased["foo"], 1);
+    assert_eq!(cased.get("is"), None);
+    assert_eq!(cased.len(), 4);
 
 }
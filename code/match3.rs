@@ -1,9 +1,24 @@
 // match3.rs
+#[derive(Debug, PartialEq)]
+enum Category {
+    Zero(String),
+    HelloOne,
+    Other(i32,String)
+}
+
+fn classify(t: (i32,String)) -> Category {
+    match t {
+        (0, s) => Category::Zero(s),
+        (1, ref s) if s == "hello" => Category::HelloOne,
+        (n, s) => Category::Other(n, s)
+    }
+}
+
 fn match_tuple(t: (i32,String)) {
-    let text = match t {
-        (0, s) => format!("zero {}", s),
-        (1, s) if s == "hello" => format!("hello one!"),
-        tt => format!("no match {:?}", tt)
+    let text = match classify(t) {
+        Category::Zero(s) => format!("zero {}", s),
+        Category::HelloOne => format!("hello one!"),
+        Category::Other(n, s) => format!("no match {:?}", (n, s))
      };
     println!("{}", text);
 }
@@ -37,4 +52,9 @@ fn main() {
         (42,"answer") => println!("yes"),
         _ => println!("no")
     };
+
+    assert_eq!(classify(s("hello",0)), Category::Zero("hello".to_string()));
+    assert_eq!(classify(s("hello",1)), Category::HelloOne);
+    assert_eq!(classify(s("world",1)), Category::Other(1, "world".to_string()));
+    assert_eq!(classify(s("hello",42)), Category::Other(42, "hello".to_string()));
 }
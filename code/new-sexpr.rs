@@ -1,21 +1,73 @@
 // enum4.rs
+extern crate json;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 enum Value {
     Number(f64),
     Str(String),
     Bool(bool),
-    Arr(Vec<Value>)
+    Arr(Vec<Value>),
+    Func(Rc<Lambda>)
 }
 
 use std::fmt;
+use std::rc::Rc;
+
+// what (lambda (x y) body) evaluates to. The environment is the one
+// the lambda was defined in, so it can see variables that were in scope then.
+#[derive(Debug)]
+struct Lambda {
+    params: Vec<String>,
+    body: Value,
+    env: Env
+}
+
+// two functions are only the same if they are the same function
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Lambda) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Value {
+    // remove consecutive repeated elements of a list. Other values are left alone
+    fn dedup_array(&mut self) {
+        if let Value::Arr(ref mut arr) = *self {
+            arr.dedup();
+        }
+    }
+
+    // the elements of a list; other values have no members
+    fn members(&self) -> impl Iterator<Item=&Value> {
+        match *self {
+            Value::Arr(ref arr) => arr.iter(),
+            _ => [].iter()
+        }
+    }
+}
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Value::*;
         match *self {
             Number(n) => write!(f,"{} ",n),
-            Str(ref s) => write!(f,"{} ",s),
+            Str(ref s) => {
+                if needs_quotes(s) {
+                    write!(f,"\"")?;
+                    for ch in s.chars() {
+                        match ch {
+                            '"' => write!(f,"\\\"")?,
+                            '\\' => write!(f,"\\\\")?,
+                            '\n' => write!(f,"\\n")?,
+                            '\t' => write!(f,"\\t")?,
+                            c => write!(f,"{}",c)?
+                        }
+                    }
+                    write!(f,"\" ")
+                } else {
+                    write!(f,"{} ",s)
+                }
+            },
             Bool(b) => write!(f,"{} ",b),
             Arr(ref arr) => {
                 write!(f,"(")?;
@@ -23,11 +75,26 @@ impl fmt::Display for Value {
                     v.fmt(f)?;
                 }
                 write!(f,")")
+            },
+            Func(ref func) => {
+                write!(f,"(lambda (")?;
+                for p in func.params.iter() {
+                    write!(f,"{} ",p)?;
+                }
+                write!(f,")")?;
+                func.body.fmt(f)?;
+                write!(f,")")
             }
         }
     }
 }
 
+// a string only needs quoting if it would not be read back as the same word
+fn needs_quotes(s: &str) -> bool {
+    s.is_empty() || s == "T" || s == "F" || looks_like_number(s)
+        || s.chars().any(|c| c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == '\\')
+}
+
 struct Builder {
     stack: Vec<Vec<Value>>,
     current: Vec<Value>,
@@ -74,13 +141,13 @@ impl Builder {
         current
     }
 
-    fn value(&mut self) -> Result<Value,String> {
+    fn value(&mut self) -> Result<Value,SexprError> {
         match self.error {
             None => {
                 let current = self.extract_current(Vec::new());
                 Ok(Value::Arr(current))
             },
-            Some(ref s) => Err(s.clone())
+            Some(ref s) => SexprError::err(s.clone())
         }
     }
     
@@ -114,16 +181,45 @@ impl Builder {
 }
 
 
-fn parse(text: &str) -> Result<Value,String> {
+// after an opening quote: read up to the closing quote, handling escapes
+fn parse_string(chars: &mut std::str::Chars) -> Result<String,SexprError> {
+    let mut res = String::new();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => return Ok(res),
+            '\\' => match chars.next() {
+                Some('n') => res.push('\n'),
+                Some('t') => res.push('\t'),
+                Some('"') => res.push('"'),
+                Some('\\') => res.push('\\'),
+                Some(c) => return SexprError::err(format!("unknown escape \\{}", c)),
+                None => break
+            },
+            c => res.push(c)
+        }
+    }
+    SexprError::err("unterminated string".to_string())
+}
+
+fn parse(text: &str) -> Result<Value,SexprError> {
     let mut builder = Builder::new();
     let mut word = String::new();
-    for ch in text.chars() {
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
         if ch.is_whitespace() {            
             if word.len() > 0 {
                 parse_word(&mut builder, &word)?;
                 word.clear();
             }            
         } else
+        if ch == '"' {
+            if word.len() > 0 {
+                parse_word(&mut builder, &word)?;
+                word.clear();
+            }
+            let s = parse_string(&mut chars)?;
+            builder.s(&s);
+        } else
         if ch == '(' {
             builder.open();
         } else
@@ -142,17 +238,52 @@ fn parse(text: &str) -> Result<Value,String> {
 
 use std::error::Error;
 
-fn parse_word(builder: &mut Builder, word: &str) -> Result<(),String> {
-    // guaranteed to be at least one character!
-    let first = word.chars().next().unwrap();
+// 0xFF and 0b1010 are whole numbers in hex and binary
+// from_str_radix allows a sign, which makes no sense after 0x.
+// Numbers are f64, so a value with more than 53 significant bits would be
+// silently rounded; better to refuse it. (u64::MAX rounds up to 2^64, which
+// converts back to u64::MAX - hence the second check)
+fn parse_radix(word: &str, radix: u32) -> Result<f64,SexprError> {
+    let digits = &word[2..];
+    if digits.starts_with('+') || digits.starts_with('-') {
+        return SexprError::err(format!("bad number {}: unexpected sign", word));
+    }
+    match u64::from_str_radix(digits, radix) {
+        Ok(n) => {
+            let x = n as f64;
+            if x as u64 != n || x >= u64::MAX as f64 {
+                SexprError::err(format!("bad number {}: too big to be exact", word))
+            } else {
+                Ok(x)
+            }
+        },
+        Err(e) => SexprError::err(format!("bad number {}: {}", word, e))
+    }
+}
+
+// a digit, or a minus sign followed by a digit
+fn looks_like_number(word: &str) -> bool {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some('-') => chars.next().map_or(false, |c| c.is_digit(10)),
+        Some(c) => c.is_digit(10),
+        None => false
+    }
+}
+
+fn parse_word(builder: &mut Builder, word: &str) -> Result<(),SexprError> {
     if word == "T" || word == "F" {
         builder.b(word == "T");
     } else
-    if first.is_digit(10) || first == '-' {
-        match word.parse::<f64>() {
-        Ok(num) => builder.n(num),
-        Err(err) => return Err(err.description().to_string())
-        };
+    if word.starts_with("0x") || word.starts_with("0X") {
+        builder.n(parse_radix(word, 16)?);
+    } else
+    if word.starts_with("0b") || word.starts_with("0B") {
+        builder.n(parse_radix(word, 2)?);
+    } else
+    if looks_like_number(word) {
+        let num: f64 = word.parse()?;
+        builder.n(num);
     } else {
         builder.s(&word);
     }
@@ -184,7 +315,7 @@ impl <'a> Iterator for Pairs<'a> {
         let v = &self.slice[self.idx];
         self.idx += 1;
         match *v {
-            Value::Arr(ref arr) if arr.len() > 2 => {
+            Value::Arr(ref arr) if arr.len() >= 2 => {
                 match arr[0] {
                     Value::Str(ref s) => {
                         Some((s,&arr[1]))
@@ -197,6 +328,45 @@ impl <'a> Iterator for Pairs<'a> {
     }
 }
 
+use std::ops::Index;
+use std::collections::HashMap;
+
+// look up a (key value) pair by key, like a map. Panics if the key isn't there
+impl <'a> Index<&'a str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        pairs(self)
+            .and_then(|mut p| p.find(|&(k,_)| k == key))
+            .map(|(_,v)| v)
+            .expect("no such key")
+    }
+}
+
+// JSON objects become lists of (key value) pairs, so they can be indexed by key.
+// There's no null value, so null becomes the empty list, like nil in Lisp.
+fn json_to_value(j: &json::JsonValue) -> Value {
+    if let Some(b) = j.as_bool() {
+        Value::Bool(b)
+    } else
+    if let Some(x) = j.as_f64() {
+        Value::Number(x)
+    } else
+    if let Some(s) = j.as_str() {
+        Value::Str(s.to_string())
+    } else
+    if j.is_array() {
+        Value::Arr(j.members().map(json_to_value).collect())
+    } else
+    if j.is_object() {
+        Value::Arr(j.entries()
+            .map(|(k,v)| Value::Arr(vec![Value::Str(k.to_string()), json_to_value(v)]))
+            .collect())
+    } else {
+        Value::Arr(Vec::new())
+    }
+}
+
 #[derive(Debug)]
 pub struct SexprError {
     details: String
@@ -231,42 +401,142 @@ impl From<std::num::ParseFloatError> for SexprError {
 }
 
 
-fn eval(v: &Value) -> Result<f64,SexprError> {
+type Builtin = fn(&[f64]) -> Result<f64,SexprError>;
+
+fn add(args: &[f64]) -> Result<f64,SexprError> {
+    Ok(args.iter().sum())
+}
+
+fn mul(args: &[f64]) -> Result<f64,SexprError> {
+    Ok(args.iter().product())
+}
+
+fn two_args(args: &[f64]) -> Result<(f64,f64),SexprError> {
+    if args.len() != 2 {
+        return SexprError::err(format!("expected two arguments, got {}", args.len()));
+    }
+    Ok((args[0], args[1]))
+}
+
+fn sub(args: &[f64]) -> Result<f64,SexprError> {
+    let (x,y) = two_args(args)?;
+    Ok(x - y)
+}
+
+fn div(args: &[f64]) -> Result<f64,SexprError> {
+    let (x,y) = two_args(args)?;
+    if y == 0.0 {
+        return SexprError::err("division by zero".to_string());
+    }
+    Ok(x / y)
+}
+
+// The operators that eval knows about, which callers can add to,
+// and the variables that are currently defined.
+#[derive(Debug, Clone)]
+struct Env {
+    ops: HashMap<String,Builtin>,
+    vars: HashMap<String,Value>
+}
+
+impl Env {
+    fn new() -> Env {
+        let mut env = Env{ops: HashMap::new(), vars: HashMap::new()};
+        env.register("+", add);
+        env.register("*", mul);
+        env.register("-", sub);
+        env.register("/", div);
+        env
+    }
+
+    fn register(&mut self, name: &str, op: Builtin) -> &mut Env {
+        self.ops.insert(name.to_string(), op);
+        self
+    }
+
+    fn define(&mut self, name: &str, v: Value) -> &mut Env {
+        self.vars.insert(name.to_string(), v);
+        self
+    }
+}
+
+// a (lambda (params...) body) form, a function value,
+// or the name of a variable holding a function
+fn eval_func(v: &Value, env: &Env) -> Result<Rc<Lambda>,SexprError> {
     match *v {
-        Value::Arr(ref arr) if arr.len() > 2 => {
-            match arr[0] {
-                Value::Str(ref s) => {
-                    if s == "+" || s == "*" {
-                        let adding = s == "+";
-                        let mut res = if adding {0.0} else {1.0};
-                        for v in &arr[1..] {
-                            let num = eval(v)?;
-                            res = if adding {
-                                res + num
-                            } else {
-                                res * num
-                            }
-                        }
-                        Ok(res)
-                    } else
-                    if s == "-" || s == "/" {
-                        let x = eval(&arr[1])?;
-                        let y = eval(&arr[2])?;
-                        let res = if s == "-" {
-                            x - y
-                        } else {
-                            x / y
-                        };
-                        Ok(res)
-                    } else {
-                        SexprError::err(format!("unknown operator {:?}", s))
+        Value::Func(ref func) => Ok(func.clone()),
+        Value::Arr(ref arr) if arr.len() == 3 && arr[0] == Value::Str("lambda".to_string()) => {
+            let mut params = Vec::new();
+            if let Value::Arr(ref names) = arr[1] {
+                for name in names {
+                    match *name {
+                        Value::Str(ref s) => params.push(s.clone()),
+                        ref v => return SexprError::err(format!("parameter must be a name {:?}", v))
                     }
-                },
-                ref v => SexprError::err(format!("operator must be string {:?}", v))
+                }
+            } else {
+                return SexprError::err(format!("lambda needs a parameter list {:?}", arr[1]));
+            }
+            Ok(Rc::new(Lambda{params: params, body: arr[2].clone(), env: env.clone()}))
+        },
+        Value::Str(ref s) => match env.vars.get(s) {
+            Some(&Value::Func(ref func)) => Ok(func.clone()),
+            _ => SexprError::err(format!("unknown operator {:?}", s))
+        },
+        ref v => SexprError::err(format!("not a function {:?}", v))
+    }
+}
+
+// evaluating a lambda form gives a function, which can then be defined in an Env
+fn lambda(v: &Value, env: &Env) -> Result<Value,SexprError> {
+    Ok(Value::Func(eval_func(v, env)?))
+}
+
+fn apply(func: &Lambda, args: &[f64]) -> Result<f64,SexprError> {
+    if args.len() != func.params.len() {
+        return SexprError::err(format!("expected {} arguments, got {}", func.params.len(), args.len()));
+    }
+    let mut env = func.env.clone();
+    for (name,&arg) in func.params.iter().zip(args) {
+        env.define(name, Value::Number(arg));
+    }
+    eval(&func.body, &env)
+}
+
+// numbers are numbers, strings may look like numbers, and booleans count as 0 or 1.
+// 'inf' and 'NaN' parse as f64, but nobody means them as numbers here
+fn coerce_number(v: &Value) -> Option<f64> {
+    match *v {
+        Value::Number(x) => Some(x),
+        Value::Str(ref s) => s.trim().parse::<f64>().ok().filter(|x| x.is_finite()),
+        Value::Bool(b) => Some(if b {1.0} else {0.0}),
+        _ => None
+    }
+}
+
+fn eval(v: &Value, env: &Env) -> Result<f64,SexprError> {
+    match *v {
+        Value::Arr(ref arr) if arr.len() > 1 => {
+            let mut args = Vec::new();
+            for v in &arr[1..] {
+                args.push(eval(v, env)?);
+            }
+            if let Value::Str(ref s) = arr[0] {
+                if let Some(op) = env.ops.get(s) {
+                    return op(&args);
+                }
             }
+            let func = eval_func(&arr[0], env)?;
+            apply(&func, &args)
+        },
+        Value::Str(ref s) if env.vars.contains_key(s) => match env.vars[s] {
+            Value::Number(x) => Ok(x),
+            _ => SexprError::err(format!("cannot convert {:?} to number", v))
         },
-        Value::Number(x) => Ok(x),
-        ref v => SexprError::err(format!("cannot convert {:?} to number", v))
+        ref v => match coerce_number(v) {
+            Some(x) => Ok(x),
+            None => SexprError::err(format!("cannot convert {:?} to number", v))
+        }
     }
 }
 
@@ -310,7 +580,105 @@ fn main() {
         //~ println!("{} {}",s,e);
     //~ }
 
-    let x = eval(&res);
+    let mut env = Env::new();
+    let x = eval(&res, &env);
     println!("result is {:?}",x);
-    
+
+    let doc = json::parse(r#"
+    {
+        "code": 200,
+        "success": true,
+        "payload": {
+            "features": ["awesome", "easyAPI", "lowLearningCurve"],
+            "bugs": null
+        }
+    }
+    "#).expect("parse failed");
+    let v = json_to_value(&doc);
+    println!("{}",v);
+    if let Value::Number(code) = v["code"] {
+        assert_eq!(code, 200.0);
+    } else {
+        panic!("code should be a number");
+    }
+    assert_eq!(v["payload"]["features"].members().count(), 3);
+    assert_eq!(v["code"].members().count(), 0);
+
+    let abc = parse("(a b c)").expect("error");
+    let names: Vec<String> = abc.members().map(|m| m.to_string()).collect();
+    assert_eq!(names, ["a ", "b ", "c "]);
+
+    let mut nums = parse("(1 1 2 2 2 3 1)").expect("error");
+    nums.dedup_array();
+    assert_eq!(nums, parse("(1 2 3 1)").expect("error"));
+    let mut num = Value::Number(1.0);
+    num.dedup_array();
+    assert_eq!(num, Value::Number(1.0));
+
+    let flags = parse("(flags 0xFF 0b1010)").expect("error");
+    assert_eq!(flags, Value::Arr(vec![Value::Str("flags".to_string()), Value::Number(255.0), Value::Number(10.0)]));
+    assert!(parse("(0xFG)").is_err());
+    assert!(parse("(0b102)").is_err());
+    assert!(parse("(0x1FFFFFFFFFFFFFFFF)").is_err());
+    assert!(parse("(0x+1)").is_err());
+    assert!(parse("(0b-1)").is_err());
+    assert_eq!(parse("(0x20000000000000)").expect("error"), Value::Arr(vec![Value::Number(9007199254740992.0)]));
+    assert!(parse("(0x20000000000001)").is_err());
+    assert_eq!(parse("(0x1000000000000000)").expect("error"), Value::Arr(vec![Value::Number(1152921504606846976.0)]));
+    assert!(parse("(0xFFFFFFFFFFFFFFFF)").is_err());
+
+    fn avg(args: &[f64]) -> Result<f64,SexprError> {
+        if args.is_empty() {
+            return SexprError::err("avg of nothing".to_string());
+        }
+        Ok(args.iter().sum::<f64>() / args.len() as f64)
+    }
+    env.register("avg", avg);
+    let res = eval(&parse("(avg 2 4 6)").expect("error"), &env).expect("eval error");
+    assert_eq!(res, 4.0);
+    let res = eval(&parse("(- (* 2 (avg 1 3)) 1)").expect("error"), &env).expect("eval error");
+    assert_eq!(res, 3.0);
+    assert!(eval(&parse("(max 1 2)").expect("error"), &env).is_err());
+
+    let err = eval(&parse("(/ 5 0)").expect("error"), &env).unwrap_err();
+    assert_eq!(err.to_string(), "division by zero");
+    assert_eq!(eval(&parse("(/ 5 2)").expect("error"), &env).expect("eval error"), 2.5);
+
+    let res = eval(&parse("((lambda (x) (+ x 1)) 5)").expect("error"), &env).expect("eval error");
+    assert_eq!(res, 6.0);
+    let inc = lambda(&parse("(lambda (x) (+ x 1))").expect("error"), &env).expect("not a lambda");
+    println!("inc is {}",inc);
+    env.define("inc", inc);
+    assert_eq!(eval(&parse("(inc (inc 5))").expect("error"), &env).expect("eval error"), 7.0);
+    // the lambda keeps the n it was defined with
+    env.define("n", Value::Number(10.0));
+    let add_n = lambda(&parse("(lambda (x) (+ x n))").expect("error"), &env).expect("not a lambda");
+    env.define("add_n", add_n);
+    env.define("n", Value::Number(100.0));
+    assert_eq!(eval(&parse("(add_n 1)").expect("error"), &env).expect("eval error"), 11.0);
+    assert!(eval(&parse("(inc 1 2)").expect("error"), &env).is_err());
+
+    assert_eq!(coerce_number(&Value::Number(1.5)), Some(1.5));
+    assert_eq!(coerce_number(&Value::Str("3.5".to_string())), Some(3.5));
+    assert_eq!(coerce_number(&Value::Str(" -2 ".to_string())), Some(-2.0));
+    assert_eq!(coerce_number(&Value::Str("three".to_string())), None);
+    assert_eq!(coerce_number(&Value::Str("inf".to_string())), None);
+    assert_eq!(coerce_number(&Value::Bool(true)), Some(1.0));
+    assert_eq!(coerce_number(&Value::Bool(false)), Some(0.0));
+    assert_eq!(coerce_number(&Value::Arr(Vec::new())), None);
+    assert_eq!(eval(&parse(r#"(+ "2" 3)"#).expect("error"), &env).expect("error"), 5.0);
+    assert_eq!(eval(&parse("(+ T T)").expect("error"), &env).expect("error"), 2.0);
+    assert!(eval(&parse(r#"(+ "two" 3)"#).expect("error"), &env).is_err());
+
+    let quoted = parse(r#"(say "hello \"dolly\"\n" "")"#).expect("error");
+    assert_eq!(quoted, Value::Arr(vec![
+        Value::Str("say".to_string()), Value::Str("hello \"dolly\"\n".to_string()), Value::Str("".to_string())
+    ]));
+    for s in &["he said \"hi\"", "line1\nline2\tend", "back\\slash", "", "two words", "42", "T", "(paren", "plain"] {
+        let v = Value::Arr(vec![Value::Str(s.to_string()), Value::Number(1.0)]);
+        let text = v.to_string();
+        assert_eq!(parse(&text).expect("error"), v, "round trip of {}", text);
+    }
+    assert!(parse(r#"("no end)"#).is_err());
+    assert!(parse(r#"("bad \q")"#).is_err());
 }
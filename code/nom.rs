@@ -4,78 +4,247 @@ extern crate nom;
 #[macro_use]
 extern crate approx;
 
-use nom::digit;
+use nom::{digit,IResult};
 use std::str::FromStr;
+use std::io;
+use std::io::prelude::*;
 
-fn main() {
+named!(signed_digits<&str,(Option<&str>,&str)>,
+    pair!(
+        opt!(alt!(tag!("+") | tag!("-"))),  // maybe sign?
+        digit
+    )
+);
+
+named!(maybe_signed_digits<&str,&str>,
+    recognize!(signed_digits)
+);
 
-    named!(signed_digits<&str,(Option<&str>,&str)>,
-        pair!(
-            opt!(alt!(tag!("+") | tag!("-"))),  // maybe sign?
-            digit
+named!(floating_point<&str,&str>,
+    recognize!(
+        tuple!(
+            maybe_signed_digits,
+            opt!(complete!(pair!(
+                tag_s!("."),
+                digit
+            ))),
+            opt!(complete!(pair!(
+                alt!(tag_s!("e") | tag_s!("E")),
+                maybe_signed_digits
+            )))
         )
-    );
+    )
+);
 
-    named!(maybe_signed_digits<&str,&str>,
-        recognize!(signed_digits)
-    );
+named!(float64<&str,f64>,
+    map_res!(floating_point, FromStr::from_str)
+);
 
-    named!(floating_point<&str,&str>,
-        recognize!(
-            tuple!(
-                maybe_signed_digits,
-                opt!(complete!(pair!(
-                    tag_s!("."),
-                    digit
-                ))),
-                opt!(complete!(pair!(
-                    alt!(tag_s!("e") | tag_s!("E")),
-                    maybe_signed_digits
-                )))
-            )
-        )
-    );
+named!(factor<&str,f64>,
+    alt!(
+        ws!(float64) |
+        ws!(delimited!( tag_s!("("), expr, tag_s!(")") ))
+    )
+);
 
-    named!(float64<&str,f64>,
-        map_res!(floating_point, FromStr::from_str)
-    );
+named!(term<&str,f64>, do_parse!(
+    init: factor >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("*") | tag_s!("/")),
+            factor
+        ),
+        init,
+        |acc, v:(_,f64)| {
+            if v.0 == "*" {acc * v.1} else {acc / v.1}
+        }
+    )
+    >> (res)
+));
 
-    named!(factor<&str,f64>,
-        alt!(
-            ws!(float64) |
-            ws!(delimited!( tag_s!("("), expr, tag_s!(")") ))
-        )
-    );
+named!(expr<&str,f64>, do_parse!(
+    init: term >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("+") | tag_s!("-")),
+            term
+        ),
+        init,
+        |acc, v:(_,f64)| {
+            if v.0 == "+" {acc + v.1} else {acc - v.1}
+        }
+    )
+    >> (res)
+));
 
-    named!(term<&str,f64>, do_parse!(
-        init: factor >>
-        res: fold_many0!(
-            tuple!(
-                alt!(tag_s!("*") | tag_s!("/")),
-                factor
-            ),
-            init,
-            |acc, v:(_,f64)| {
-                if v.0 == "*" {acc * v.1} else {acc / v.1}
-            }
-        )
-        >> (res)
-    ));
-
-    named!(expr<&str,f64>, do_parse!(
-        init: term >>
-        res: fold_many0!(
-            tuple!(
-                alt!(tag_s!("+") | tag_s!("-")),
-                term
-            ),
-            init,
-            |acc, v:(_,f64)| {
-                if v.0 == "+" {acc + v.1} else {acc - v.1}
+// the same grammar over integers. Division truncates, and the checked
+// operations give None for division by zero (or overflow), which
+// expr_opt! turns into a parse error
+named!(int64<&str,i64>,
+    map_res!(maybe_signed_digits, FromStr::from_str)
+);
+
+named!(int_factor<&str,i64>,
+    alt!(
+        ws!(int64) |
+        ws!(delimited!( tag_s!("("), int_expr, tag_s!(")") ))
+    )
+);
+
+named!(int_term<&str,i64>, do_parse!(
+    init: int_factor >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("*") | tag_s!("/") | tag_s!("%")),
+            int_factor
+        ),
+        Some(init),
+        |acc: Option<i64>, v:(&str,i64)| acc.and_then(|a| match v.0 {
+            "*" => a.checked_mul(v.1),
+            "/" => a.checked_div(v.1),
+            _ => a.checked_rem(v.1)
+        })
+    ) >>
+    val: expr_opt!(res)
+    >> (val)
+));
+
+named!(int_expr<&str,i64>, do_parse!(
+    init: int_term >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("+") | tag_s!("-")),
+            int_term
+        ),
+        Some(init),
+        |acc: Option<i64>, v:(&str,i64)| acc.and_then(|a| {
+            if v.0 == "+" {a.checked_add(v.1)} else {a.checked_sub(v.1)}
+        })
+    ) >>
+    val: expr_opt!(res)
+    >> (val)
+));
+
+// Rather than evaluating as we go, these parsers build a tree of the expression
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(f64),
+    Add(Box<Expr>,Box<Expr>),
+    Sub(Box<Expr>,Box<Expr>),
+    Mul(Box<Expr>,Box<Expr>),
+    Div(Box<Expr>,Box<Expr>)
+}
+
+fn binary(op: &str, left: Expr, right: Expr) -> Expr {
+    let (l, r) = (Box::new(left), Box::new(right));
+    match op {
+        "+" => Expr::Add(l, r),
+        "-" => Expr::Sub(l, r),
+        "*" => Expr::Mul(l, r),
+        _ => Expr::Div(l, r)
+    }
+}
+
+named!(ast_factor<&str,Expr>,
+    alt!(
+        map!(ws!(float64), Expr::Num) |
+        ws!(delimited!( tag_s!("("), ast_expr, tag_s!(")") ))
+    )
+);
+
+named!(ast_term<&str,Expr>, do_parse!(
+    init: ast_factor >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("*") | tag_s!("/")),
+            ast_factor
+        ),
+        init,
+        |acc, v:(&str,Expr)| binary(v.0, acc, v.1)
+    )
+    >> (res)
+));
+
+named!(ast_expr<&str,Expr>, do_parse!(
+    init: ast_term >>
+    res: fold_many0!(
+        tuple!(
+            alt!(tag_s!("+") | tag_s!("-")),
+            ast_term
+        ),
+        init,
+        |acc, v:(&str,Expr)| binary(v.0, acc, v.1)
+    )
+    >> (res)
+));
+
+// the operator and its precedence; a number binds tightest of all
+fn op_info(expr: &Expr) -> (&'static str, u8) {
+    match *expr {
+        Expr::Num(_) => ("", 3),
+        Expr::Add(..) => ("+", 1),
+        Expr::Sub(..) => ("-", 1),
+        Expr::Mul(..) => ("*", 2),
+        Expr::Div(..) => ("/", 2)
+    }
+}
+
+// Write the expression back out with as few parentheses as possible.
+// A child needs them if it binds more loosely than its parent. Since the
+// operators associate to the left, a right child of the same precedence
+// also needs them: 1 - (2 - 3) is not 1 - 2 - 3
+fn to_string(expr: &Expr) -> String {
+    let (op, prec) = op_info(expr);
+    let (left, right) = match *expr {
+        Expr::Num(x) => return x.to_string(),
+        Expr::Add(ref l, ref r) | Expr::Sub(ref l, ref r) |
+        Expr::Mul(ref l, ref r) | Expr::Div(ref l, ref r) => (l, r)
+    };
+    let wrap = |e: &Expr, needs_parens: bool| {
+        if needs_parens {
+            format!("({})", to_string(e))
+        } else {
+            to_string(e)
+        }
+    };
+    format!("{} {} {}",
+        wrap(left, op_info(left).1 < prec),
+        op,
+        wrap(right, op_info(right).1 <= prec))
+}
+
+// evaluate a whole line; anything left over after the expression is an error
+fn eval_line(s: &str) -> Result<f64,String> {
+    match expr(s.trim()) {
+        IResult::Done(rest, v) => if rest.trim().is_empty() {
+            Ok(v)
+        } else {
+            Err(format!("unexpected '{}'", rest))
+        },
+        IResult::Error(e) => Err(format!("parse error {:?}", e)),
+        IResult::Incomplete(_) => Err("incomplete expression".to_string())
+    }
+}
+
+fn repl() {
+    let stdin = io::stdin();
+    print!("> ");
+    io::stdout().flush().unwrap();
+    for line in stdin.lock().lines() {
+        let line = line.expect("cannot read stdin");
+        if ! line.trim().is_empty() {
+            match eval_line(&line) {
+                Ok(v) => println!("{}", v),
+                Err(e) => println!("error: {}", e)
             }
-        )
-        >> (res)
-    ));
+        }
+        print!("> ");
+        io::stdout().flush().unwrap();
+    }
+    println!();
+}
+
+fn main() {
 
     macro_rules! expr_eq {
         ($e:expr) => (assert_relative_eq!(
@@ -91,6 +260,13 @@ fn main() {
     expr_eq!(2.2*(1.1 + 4.5)/3.4);
     expr_eq!((1.0 + 2.0)*(3.0 + 4.0*(5.0 + 6.0)));
 
+    // operators of the same precedence must associate to the left
+    expr_eq!(10.0 - 2.0 - 3.0);
+    expr_eq!(2.0 - 3.0 + 4.0);
+    expr_eq!(24.0 / 4.0 / 2.0);
+    expr_eq!(8.0 / 2.0 * 4.0);
+    assert_relative_eq!(expr("10-2-3").to_result().unwrap(), 5.0);
+
 
 
     named!(fold_sum<&str,f64>,
@@ -106,5 +282,32 @@ fn main() {
 
     assert_relative_eq!(fold_sum("1 2 3").to_result().unwrap(), 6.0);
 
+    assert_relative_eq!(eval_line("2 + 3 * 4").unwrap(), 14.0);
+    assert!(eval_line("2 +").is_err());
+    assert!(eval_line("2 3").is_err());
+
+    assert_eq!(int_expr("7 / 2").to_result().unwrap(), 3);
+    assert_eq!(int_expr("7 % 3").to_result().unwrap(), 1);
+    assert_eq!(int_expr("6 / 4 + 2 * (10 % 4)").to_result().unwrap(), 5);
+    assert_eq!(int_expr("-7 / 2").to_result().unwrap(), -3);
+    assert!(int_expr("1 / 0").to_result().is_err());
+    assert!(int_expr("1 % (2 - 2)").to_result().is_err());
+
+    let num = |x| Box::new(Expr::Num(x));
+    let e = Expr::Add(Box::new(Expr::Mul(num(2.0), num(3.0))), num(4.0));
+    assert_eq!(to_string(&e), "2 * 3 + 4");
+    let e = Expr::Mul(Box::new(Expr::Add(num(2.0), num(3.0))), num(4.0));
+    assert_eq!(to_string(&e), "(2 + 3) * 4");
+    for text in &["2 * 3 + 4", "(2 + 3) * 4", "10 - (2 - 3)", "10 - 2 - 3", "1 / (2 * 3)",
+                  "((1 + 2)) * (3 - 4) / 5", "2 - -1.5", "(((7)))"] {
+        let ast = ast_expr(text).to_result().unwrap();
+        let canon = to_string(&ast);
+        assert_eq!(ast_expr(&canon).to_result().unwrap(), ast, "{} -> {}", text, canon);
+        assert_relative_eq!(expr(&canon).to_result().unwrap(), expr(text).to_result().unwrap());
+    }
+    assert_eq!(to_string(&ast_expr("((1 + 2)) * (3 - 4) / 5").to_result().unwrap()), "(1 + 2) * (3 - 4) / 5");
+
+    repl();
+
 }
 
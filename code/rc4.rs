@@ -1,12 +1,14 @@
 // rc2.rs
 use std::rc::Rc;
 use std::rc::Weak;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 struct Parent {
     firstname: String,
     surname: String,
-    children: Vec<Rc<Parent>>,
+    children: RefCell<Vec<Rc<Parent>>>,
     mother: Weak<Parent>,
     father: Weak<Parent>
     
@@ -17,35 +19,82 @@ impl Parent {
         Parent {
             firstname: first.to_string(),
             surname: last.to_string(),
-            children: Vec::new(),
+            children: RefCell::new(Vec::new()),
             mother: Weak::new(),
             father: Weak::new(),
         }
     }
+
+    // parents, then grandparents, and so on. Parents that have been
+    // dropped are skipped, and nobody is listed twice
+    fn ancestors(&self) -> Vec<Rc<Parent>> {
+        let mut res: Vec<Rc<Parent>> = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(self.father.clone());
+        queue.push_back(self.mother.clone());
+        while let Some(parent) = queue.pop_front() {
+            if let Some(parent) = parent.upgrade() {
+                if res.iter().any(|p| Rc::ptr_eq(p, &parent)) {
+                    continue;
+                }
+                queue.push_back(parent.father.clone());
+                queue.push_back(parent.mother.clone());
+                res.push(parent);
+            }
+        }
+        res
+    }
+
+    fn collect_descendants(&self, seen: &mut Vec<Rc<Parent>>) {
+        for child in self.children.borrow().iter() {
+            // a child may be reachable through more than one of our descendants
+            if ! seen.iter().any(|p| Rc::ptr_eq(p, child)) {
+                seen.push(child.clone());
+                child.collect_descendants(seen);
+            }
+        }
+    }
+
+    fn descendant_count(&self) -> usize {
+        let mut seen = Vec::new();
+        self.collect_descendants(&mut seen);
+        seen.len()
+    }
    
 }
 
+// The child must know its parents before it goes into an Rc, since after that
+// it can't be changed. The parents are already shared, so their children
+// need a RefCell.
+fn add_child(father: &Rc<Parent>, mother: &Rc<Parent>, mut child: Parent) -> Rc<Parent> {
+    child.father = Rc::downgrade(father);
+    child.mother = Rc::downgrade(mother);
+    let child = Rc::new(child);
+    father.children.borrow_mut().push(child.clone());
+    mother.children.borrow_mut().push(child.clone());
+    child
+}
+
 fn main() {
+    // the grandparents
+    let grandad = Rc::new(Parent::new("Bill","Smith"));
+    let granny = Rc::new(Parent::new("Jane","Smith"));
     // the parents
-    let mut father = Parent::new("John","Smith");
-    let mut mother = Parent::new("Mary","Jones");
-    // the kids
-    let mut bob = Parent::new("Bob","Smith");
-    let mut alice = Parent::new("Alice","Smith");
-    // father gets a vector of strong references to his kids
-    father.children = vec! [Rc::new(bob),Rc::new(alice)];
-
-    // mother gets a copy!
-    mother.children = father.children.clone();
-    
-    let father_ref = Rc::new(father);
-    let mother_ref = Rc::new(mother);
-    bob.mother = Rc::downgrade(&mother_ref);
-    bob.father = Rc::downgrade(&father_ref);
-    alice.mother = Rc::downgrade(&mother_ref);
-    alice.father = Rc::downgrade(&father_ref);
+    let father = add_child(&grandad, &granny, Parent::new("John","Smith"));
+    let mother = Rc::new(Parent::new("Mary","Jones"));
+    // the kids; both parents get strong references to them
+    let bob = add_child(&father, &mother, Parent::new("Bob","Smith"));
+    let alice = add_child(&father, &mother, Parent::new("Alice","Smith"));
 
     assert_eq! (alice.father.upgrade().unwrap().surname, "Smith");
+    assert_eq! (bob.mother.upgrade().unwrap().firstname, "Mary");
+    assert_eq! (mother.children.borrow().len(), 2);
+
+    let names: Vec<_> = alice.ancestors().iter().map(|p| p.firstname.clone()).collect();
+    assert_eq! (names, ["John","Mary","Bill","Jane"]);
+    assert_eq! (grandad.descendant_count(), 3);
+    assert_eq! (mother.descendant_count(), 2);
+    assert_eq! (bob.descendant_count(), 0);
     
-    println!("mother {:#?}",mother_ref);
+    println!("mother {:#?}",mother);
 }
@@ -1,29 +1,111 @@
 // server2.rs
+#[macro_use]
+extern crate serde_json;
+
 use std::net::{TcpListener, TcpStream};
 use std::io::prelude::*;
 use std::io;
+use std::env;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Each request is a JSON object on one line. The response is the same
+// object with "received" set, or an object with an "error" message.
+fn handle_line(line: &str) -> String {
+    let response = match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(serde_json::Value::Object(mut map)) => {
+            map.insert("received".to_string(), json!(true));
+            serde_json::Value::Object(map)
+        },
+        Ok(_) => json!({"error": "expected a JSON object"}),
+        Err(e) => json!({"error": e.to_string()})
+    };
+    response.to_string()
+}
 
-fn handle_connection(stream: TcpStream) -> io::Result<()>{
+// answer and log each line until the client hangs up.
+// A client that stays silent gets disconnected after `timeout`
+fn handle_connection(stream: TcpStream, timeout: Duration) -> io::Result<()>{
+    stream.set_read_timeout(Some(timeout))?;
+    let mut out = stream.try_clone()?;
     let mut rdr = io::BufReader::new(stream);
     let mut text = String::new();
-    rdr.read_line(&mut text)?;
-    println!("got '{}'",text.trim_right());
-    Ok(())
+    loop {
+        text.clear();
+        match rdr.read_line(&mut text) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {},
+            // Unix reports WouldBlock, Windows TimedOut
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+                println!("timed out after {:?}",timeout);
+                return Ok(());
+            },
+            Err(e) => return Err(e)
+        }
+        println!("got '{}'",text.trim_right());
+        let response = handle_line(&text);
+        out.write_all(response.as_bytes())?;
+        out.write_all(b"\n")?;
+    }
 }
 
-fn main() {
-
-    let listener = TcpListener::bind("127.0.0.1:8000").expect("could not start server");
-
-    // accept connections and get a TcpStream
+// accept connections one at a time, forever
+fn run(listener: TcpListener, timeout: Duration) -> io::Result<()> {
     for connection in listener.incoming() {
         match connection {
             Ok(stream) => {
-                if let Err(e) = handle_connection(stream) {
-                    println!("eror {:?}",e);
+                if let Err(e) = handle_connection(stream, timeout) {
+                    println!("error {:?}",e);
                 }
             }
             Err(e) => { print!("connection failed {}\n",e); }
         }
     }
+    Ok(())
+}
+
+// a bind failure (say the port is taken) is returned to the caller
+fn serve(addr: &str, timeout: Duration) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    run(listener, timeout)
+}
+
+fn main() {
+    let secs = env::args().nth(1).map(|s| s.parse().expect("timeout in seconds")).unwrap_or(10);
+    let timeout = Duration::from_secs(secs);
+    let addr = env::args().nth(2).unwrap_or("127.0.0.1:8000".to_string());
+
+    let ok: serde_json::Value = serde_json::from_str(&handle_line(r#"{"name": "bob", "age": 42}"#)).unwrap();
+    assert_eq!(ok, json!({"name": "bob", "age": 42, "received": true}));
+    let bad: serde_json::Value = serde_json::from_str(&handle_line("{name: bob")).unwrap();
+    assert!(bad["error"].is_string());
+    let not_object: serde_json::Value = serde_json::from_str(&handle_line("[1,2]")).unwrap();
+    assert_eq!(not_object["error"], "expected a JSON object");
+
+    // port 0 lets the system pick a free port; ask the listener which one
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+    let local = listener.local_addr().expect("no local address");
+
+    // a client that says nothing is cut off once the timeout passes
+    let silent = TcpStream::connect(local).expect("could not connect");
+    let (stream, _) = listener.accept().expect("could not accept");
+    let start = Instant::now();
+    handle_connection(stream, Duration::from_millis(200)).expect("timeout is not an error");
+    let waited = start.elapsed();
+    assert!(waited >= Duration::from_millis(150) && waited < Duration::from_secs(2));
+    let mut rest = String::new();
+    assert_eq!((&silent).read_to_string(&mut rest).expect("could not read"), 0);
+
+    thread::spawn(move || run(listener, timeout));
+    let mut client = TcpStream::connect(local).expect("could not connect");
+    client.write_all(b"{\"hello\":1}\n").expect("could not write");
+    let mut reply = String::new();
+    io::BufReader::new(&client).read_line(&mut reply).expect("could not read");
+    assert_eq!(reply, "{\"hello\":1,\"received\":true}\n");
+    drop(client);
+
+    if let Err(e) = serve(&addr, timeout) {
+        println!("could not serve on {}: {}",addr,e);
+        std::process::exit(1);
+    }
 }
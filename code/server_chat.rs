@@ -0,0 +1,101 @@
+// server_chat.rs
+use std::net::{TcpListener, TcpStream};
+use std::io::prelude::*;
+use std::io;
+use std::thread;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// every connected client, with an id so we know who sent what.
+// Writing to a &TcpStream doesn't need it to be mutable
+type Clients = Arc<Mutex<Vec<(usize,TcpStream)>>>;
+
+// send a line to everyone except the sender. If a write fails,
+// that client has gone away, so drop it from the list
+fn broadcast(clients: &Clients, from: usize, line: &str) {
+    let mut clients = clients.lock().unwrap();
+    clients.retain(|&(id, ref stream)| {
+        let mut stream = stream;
+        id == from || stream.write_all(line.as_bytes()).is_ok()
+    });
+}
+
+fn handle_client(stream: TcpStream, id: usize, clients: Clients) -> io::Result<()> {
+    let mut text = String::new();
+    let mut rdr = io::BufReader::new(stream);
+    while rdr.read_line(&mut text)? > 0 {
+        println!("client {} says {}",id,text.trim_end());
+        broadcast(&clients, id, &format!("{}: {}",id,text));
+        text.clear();
+    }
+    Ok(())
+}
+
+// The client is added to the list before it is told its id, all under the lock,
+// so no broadcast can slip in between. Then it gets its own thread.
+fn serve(listener: TcpListener, clients: Clients) {
+    for (id, stream) in listener.incoming().enumerate() {
+        match stream.and_then(|s| s.try_clone().map(|w| (s, w))) {
+            Ok((stream, writer)) => {
+                let clients = clients.clone();
+                {
+                    let mut list = clients.lock().unwrap();
+                    list.push((id, writer));
+                    let mut writer = &list.last().unwrap().1;
+                    if writeln!(writer, "welcome {}",id).is_err() {
+                        list.pop();
+                        continue;
+                    }
+                }
+                thread::spawn(move || {
+                    if let Err(e) = handle_client(stream, id, clients.clone()) {
+                        println!("error {:?}",e);
+                    }
+                    clients.lock().unwrap().retain(|&(other,_)| other != id);
+                    println!("client {} left",id);
+                });
+            }
+            Err(e) => { print!("connection failed {}\n",e); }
+        }
+    }
+}
+
+// each test client keeps the one BufReader, so nothing it has buffered is lost
+fn read_line<R: BufRead>(rdr: &mut R) -> String {
+    let mut line = String::new();
+    rdr.read_line(&mut line).expect("could not read");
+    line
+}
+
+fn main() {
+    let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+
+    // try it out with two clients on a port chosen by the system
+    let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind");
+    let local = listener.local_addr().expect("no local address");
+    let test_clients = clients.clone();
+    thread::spawn(move || serve(listener, test_clients));
+
+    let mut alice = io::BufReader::new(TcpStream::connect(local).expect("could not connect"));
+    assert_eq!(read_line(&mut alice), "welcome 0\n");
+    let mut bob = io::BufReader::new(TcpStream::connect(local).expect("could not connect"));
+    assert_eq!(read_line(&mut bob), "welcome 1\n");
+    alice.get_mut().write_all(b"hello bob\n").expect("could not write");
+    assert_eq!(read_line(&mut bob), "0: hello bob\n");
+    bob.get_mut().write_all(b"hi alice\n").expect("could not write");
+    assert_eq!(read_line(&mut alice), "1: hi alice\n");
+
+    // once bob hangs up, that client is pruned from the list
+    drop(bob);
+    for _ in 0..50 {
+        if clients.lock().unwrap().len() == 1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(clients.lock().unwrap().len(), 1);
+    drop(alice);
+
+    let listener = TcpListener::bind("127.0.0.1:8000").expect("could not start server");
+    serve(listener, Arc::new(Mutex::new(Vec::new())));
+}
@@ -2,28 +2,43 @@
 use std::net::{TcpListener, TcpStream};
 use std::io::prelude::*;
 use std::io;
+use std::thread;
 
-fn handle_connection(stream: TcpStream) -> io::Result<()>{
-    let mut ostream = stream.try_clone()?;
-    let mut rdr = io::BufReader::new(stream);
+// echo each line back until the other side closes. A final line without
+// a line ending is passed back as it is.
+fn echo_lines<R: BufRead, W: Write>(mut rdr: R, mut out: W) -> io::Result<()> {
     let mut text = String::new();
-    rdr.read_line(&mut text)?;
-    ostream.write_all(text.as_bytes())?;
-    print!("got {}",text);
+    while rdr.read_line(&mut text)? > 0 {
+        out.write_all(text.as_bytes())?;
+        println!("got {}",text.trim_end());
+        text.clear();
+    }
     Ok(())
 }
 
+fn handle_connection(stream: TcpStream) -> io::Result<()>{
+    let ostream = stream.try_clone()?;
+    echo_lines(io::BufReader::new(stream), ostream)
+}
+
 fn main() {
+    let input = io::Cursor::new("one\ntwo\n\nthree");
+    let mut output = Vec::new();
+    echo_lines(input, &mut output).expect("can't fail in memory");
+    assert_eq!(output, b"one\ntwo\n\nthree");
 
     let listener = TcpListener::bind("127.0.0.1:8000").expect("could not start server");
 
-    // accept connections and get a TcpStream
+    // accept connections and get a TcpStream.
+    // Each connection gets its own thread, so a slow client doesn't hold up the others
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
-                if let Err(e) = handle_connection(stream) {
-                    println!("eror {:?}",e);
-                }
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream) {
+                        println!("eror {:?}",e);
+                    }
+                });
             }
             Err(e) => { print!("connection failed {}\n",e); }
         }
@@ -0,0 +1,66 @@
+// server_frame.rs
+use std::net::{TcpListener, TcpStream};
+use std::io;
+use std::io::Cursor;
+use std::thread;
+
+mod frame;
+
+fn handle_connection(stream: TcpStream) -> io::Result<()>{
+    let mut ostream = stream.try_clone()?;
+    let mut rdr = io::BufReader::new(stream);
+    loop {
+        let data = match frame::read_frame(&mut rdr) {
+            Ok(data) => data,
+            // the client has hung up
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e)
+        };
+        println!("got {} bytes",data.len());
+        frame::write_frame(&mut ostream, &data)?;
+    }
+}
+
+// round-trip some frames through memory, and check that a bad length is refused
+fn check_frames() {
+    let big: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+    let mut buf = Vec::new();
+    frame::write_frame(&mut buf, b"").unwrap();
+    frame::write_frame(&mut buf, b"two\nlines").unwrap();
+    frame::write_frame(&mut buf, &big).unwrap();
+    assert_eq!(buf.len(), 3*4 + 9 + 5000);
+
+    let mut rdr = Cursor::new(buf);
+    assert_eq!(frame::read_frame(&mut rdr).unwrap(), b"");
+    assert_eq!(frame::read_frame(&mut rdr).unwrap(), b"two\nlines");
+    assert_eq!(frame::read_frame(&mut rdr).unwrap(), big);
+    let end = frame::read_frame(&mut rdr).unwrap_err();
+    assert_eq!(end.kind(), io::ErrorKind::UnexpectedEof);
+
+    // a peer claiming a 4 GiB frame
+    let mut huge = Cursor::new(vec![0xff, 0xff, 0xff, 0xff]);
+    let err = frame::read_frame(&mut huge).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let too_big = vec![0u8; frame::MAX_FRAME + 1];
+    let err = frame::write_frame(&mut Vec::new(), &too_big).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+fn main() {
+    check_frames();
+
+    let listener = TcpListener::bind("127.0.0.1:8000").expect("could not start server");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream) {
+                        println!("eror {:?}",e);
+                    }
+                });
+            }
+            Err(e) => { print!("connection failed {}\n",e); }
+        }
+    }
+}
@@ -24,6 +24,15 @@ fn make_set(words: &str) -> HashSet<String> {
     words.split_whitespace().map(|s| s.to_string()).collect()
 }
 
+// everything in either set
+fn union<T: Eq + Hash + Clone>(a: &HashSet<T>, b: &HashSet<T>) -> HashSet<T> {
+    a.union(b).cloned().to_set()
+}
+
+// everything in `a` that is not in `b`
+fn difference<T: Eq + Hash + Clone>(a: &HashSet<T>, b: &HashSet<T>) -> HashSet<T> {
+    a.difference(b).cloned().to_set()
+}
 
 fn main() {
     let fruit = make_set("apple orange pear");
@@ -33,8 +42,13 @@ fn main() {
         println!("{:?}",c);
     }
 
-    let intersect: () = fruit.intersection(&colours).cloned().to_set();
-    
+    let intersect: HashSet<String> = fruit.intersection(&colours).cloned().to_set();
+    assert_eq!(intersect, make_set("orange"));
+
+    assert_eq!(union(&fruit, &colours).len(), 6);
+    assert_eq!(difference(&fruit, &colours), make_set("apple pear"));
+    assert_eq!(difference(&colours, &fruit), make_set("brown purple yellow"));
+
     println!("{:?}",intersect);
     println!("{:?}",fruit);
     
@@ -1,4 +1,20 @@
 // string3.rs
+extern crate unicode_segmentation;
+use unicode_segmentation::UnicodeSegmentation;
+
+// byte offsets of every occurrence - these are what you slice with, not char counts
+fn find_all(haystack: &str, needle: char) -> Vec<usize> {
+    haystack.char_indices()
+        .filter(|&(_,ch)| ch == needle)
+        .map(|(idx,_)| idx)
+        .collect()
+}
+
+// what a reader would call characters: 'e' followed by a combining accent is one
+fn grapheme_count(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
 fn main() {
     let multilingual = "Hi! ¡Hola! привет!";
     for ch in multilingual.chars() {
@@ -26,5 +42,16 @@ fn main() {
     if let Some(idx) = multilingual.find('п') {
         println!("Russian hi {}",&multilingual[idx..]);
     }
-    
+
+    let twice = "привет привет";
+    let found = find_all(twice, 'п');
+    // each Cyrillic letter takes two bytes
+    assert_eq!(found, [0, 13]);
+    assert_eq!(&twice[found[1]..], "привет");
+
+    // 'é' written as 'e' plus U+0301 COMBINING ACUTE ACCENT
+    let cafe = "cafe\u{301}";
+    println!("{} chars {} graphemes {}",cafe,cafe.chars().count(),grapheme_count(cafe));
+    assert_eq!(cafe.chars().count(), 5);
+    assert_eq!(grapheme_count(cafe), 4);
 }
@@ -1,6 +1,8 @@
 // struct2.rs
+use std::str::FromStr;
+use std::cmp::Ordering;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 struct Person {
     first_name: String,
     last_name: String
@@ -32,6 +34,66 @@ impl Person {
     }
 
 }
+// sort by surname, and then by first name.
+// (deriving would compare first names first, since that's the field order)
+impl Ord for Person {
+    fn cmp(&self, other: &Person) -> Ordering {
+        self.last_name.cmp(&other.last_name)
+            .then_with(|| self.first_name.cmp(&other.first_name))
+    }
+}
+
+impl PartialOrd for Person {
+    fn partial_cmp(&self, other: &Person) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// "First Last" - exactly two names, so no middle names
+impl FromStr for Person {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        let names: Vec<_> = s.split_whitespace().collect();
+        if names.len() != 2 {
+            return Err(format!("expected 'first last', got '{}'",s));
+        }
+        Ok(Person::new(names[0],names[1]))
+    }
+}
+
+// set the names in any order, and only then make the Person
+struct PersonBuilder {
+    first_name: Option<String>,
+    last_name: Option<String>
+}
+
+impl PersonBuilder {
+    fn new() -> PersonBuilder {
+        PersonBuilder {
+            first_name: None,
+            last_name: None
+        }
+    }
+
+    fn first_name(&mut self, name: &str) -> &mut PersonBuilder {
+        self.first_name = Some(name.to_string());
+        self
+    }
+
+    fn last_name(&mut self, name: &str) -> &mut PersonBuilder {
+        self.last_name = Some(name.to_string());
+        self
+    }
+
+    fn build(&self) -> Result<Person,String> {
+        match (&self.first_name, &self.last_name) {
+            (&Some(ref first), &Some(ref last)) => Ok(Person::new(first,last)),
+            (&None, _) => Err("first name missing".to_string()),
+            (_, &None) => Err("last name missing".to_string())
+        }
+    }
+}
 
 fn main() {
     let p = Person::new("John","Smith");
@@ -45,4 +107,22 @@ fn main() {
     p.set_first_name("Alicia");
     let (first,last) = p.to_tuple();
     println!("first {} last {}",first,last);
+
+    let p = PersonBuilder::new().last_name("Smith").first_name("John").build().unwrap();
+    assert_eq!(p.full_name(), "John Smith");
+    let err = PersonBuilder::new().first_name("Madonna").build().unwrap_err();
+    assert_eq!(err, "last name missing");
+
+    let p: Person = "John Smith".parse().unwrap();
+    assert_eq!(p.first_name, "John");
+    assert_eq!(p.last_name, "Smith");
+    assert!("Madonna".parse::<Person>().is_err());
+    assert!("John Ronald Tolkien".parse::<Person>().is_err());
+
+    let mut people: Vec<Person> = ["John Smith","Alice Jones","Adam Smith"].iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+    people.sort();
+    let names: Vec<_> = people.iter().map(|p| p.full_name()).collect();
+    assert_eq!(names, ["Alice Jones","Adam Smith","John Smith"]);
 }
@@ -1,4 +1,6 @@
 // struct6.rs
+use std::collections::VecDeque;
+use std::fmt;
 
 type NodeBox = Option<Box<Node>>;
 
@@ -51,8 +53,83 @@ impl Node {
         println!("'{}'",self.payload);
         Self::maybe_visit(&self.right);
     }
+
+    fn write_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        if let Some(ref right) = self.right {
+            right.write_tree(f, depth + 1)?;
+        }
+        writeln!(f, "{}{}", "    ".repeat(depth), self.payload)?;
+        if let Some(ref left) = self.left {
+            left.write_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    // breadth-first: each level in turn, left to right
+    fn level_order(&self) -> Vec<&String> {
+        let mut res = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(self);
+        while let Some(node) = queue.pop_front() {
+            res.push(&node.payload);
+            if let Some(ref left) = node.left {
+                queue.push_back(left);
+            }
+            if let Some(ref right) = node.right {
+                queue.push_back(right);
+            }
+        }
+        res
+    }
+}
+
+// The tree turned on its side, one node per line and four spaces per level.
+// Read it with your head tilted left: the right subtree is above its parent.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+// The same tree, but each key carries a value - an ordered map.
+// Inserting an existing key replaces its value.
+#[derive(Debug)]
+struct MapNode<V> {
+    key: String,
+    value: V,
+    left: Option<Box<MapNode<V>>>,
+    right: Option<Box<MapNode<V>>>
 }
 
+impl <V> MapNode<V> {
+    fn new(key: &str, value: V) -> MapNode<V> {
+        MapNode{key: key.to_string(), value: value, left: None, right: None}
+    }
+
+    fn insert(&mut self, key: &str, value: V) {
+        if key == self.key {
+            self.value = value;
+        } else {
+            let branch = if key < &self.key {&mut self.left} else {&mut self.right};
+            match *branch {
+            Some(ref mut n) => n.insert(key, value),
+            None => *branch = Some(Box::new(Self::new(key, value))),
+            }
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&V> {
+        if key == self.key {
+            Some(&self.value)
+        } else {
+            let branch = if key < &self.key {&self.left} else {&self.right};
+            match *branch {
+            Some(ref n) => n.get(key),
+            None => None,
+            }
+        }
+    }
+}
 
 fn main() {
     let mut root = Node::new("root");    
@@ -63,4 +140,19 @@ fn main() {
 //    println!("root {:#?}",root);
 
     root.visit();
+
+    let order: Vec<_> = root.level_order().into_iter().map(|s| s.as_str()).collect();
+    assert_eq!(order, ["root", "one", "two", "four"]);
+
+    print!("{}", root);
+    assert_eq!(root.to_string(), "    two\nroot\n    one\n        four\n");
+
+    let mut ages = MapNode::new("mary", 32);
+    ages.insert("bob", 45);
+    ages.insert("zoe", 20);
+    ages.insert("bob", 46);
+    assert_eq!(ages.get("bob"), Some(&46));
+    assert_eq!(ages.get("mary"), Some(&32));
+    assert_eq!(ages.get("zoe"), Some(&20));
+    assert_eq!(ages.get("alice"), None);
 }
@@ -1,17 +1,23 @@
 // struct5.rs
+use std::collections::VecDeque;
+use std::iter::FromIterator;
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 type NodeBox<T> = Option<Box<Node<T>>>;
 
 #[derive(Debug)]
 struct Node<T> {
-    payload: T,
+    // always Some, except for a node taken apart by to_sorted_vec
+    payload: Option<T>,
+    count: usize,
     left: NodeBox<T>,
     right: NodeBox<T>
 }
 
 impl <T: PartialOrd> Node<T> {
     fn new(s: T) -> Node<T> {
-        Node{payload: s, left: None, right: None}
+        Node{payload: Some(s), count: 1, left: None, right: None}
     }
 
     fn boxer(node: Node<T>) -> NodeBox<T> {
@@ -26,21 +32,403 @@ impl <T: PartialOrd> Node<T> {
         self.right = Self::boxer(node);
     }
 
+    // walk down to the empty slot where the new node belongs.
+    // A loop rather than recursion, so long sorted runs can't overflow the stack
     fn insert(&mut self, data: T) {
-        if data < self.payload {
-            match self.left {
-            Some(ref mut Sn) => n.insert(data),
-            None => self.set_left(Self::new(data)),
+        self.insert_with(data, false);
+    }
+
+    // an equal value bumps the count of its node, rather than going on the right
+    fn insert_counted(&mut self, data: T) {
+        self.insert_with(data, true);
+    }
+
+    fn insert_with(&mut self, data: T, counting: bool) {
+        if counting && data == *self.payload() {
+            self.count += 1;
+            return;
+        }
+        let mut slot = if data < *self.payload() {
+            &mut self.left
+        } else {
+            &mut self.right
+        };
+        while let Some(ref mut node) = *slot {
+            if counting && data == *node.payload() {
+                node.count += 1;
+                return;
             }
+            slot = if data < *node.payload() {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        *slot = Self::boxer(Self::new(data));
+    }
+
+    // detach the leftmost node of a non-empty subtree, splicing its right child into its place.
+    // Like insert, this walks down with a cursor rather than recursing
+    fn take_min(mut slot: &mut NodeBox<T>) -> Box<Node<T>> {
+        while slot.as_ref().unwrap().left.is_some() {
+            slot = &mut slot.as_mut().unwrap().left;
+        }
+        let mut node = slot.take().unwrap();
+        *slot = node.right.take();
+        node
+    }
+
+    fn remove_from(mut slot: &mut NodeBox<T>, data: &T) -> bool {
+        // find the slot holding the node with `data`
+        loop {
+            let go_left = match *slot {
+                Some(ref node) => {
+                    if *data < *node.payload() {
+                        true
+                    } else if *data > *node.payload() {
+                        false
+                    } else {
+                        break;
+                    }
+                },
+                None => return false
+            };
+            let node = slot.as_mut().unwrap();
+            slot = if go_left { &mut node.left } else { &mut node.right };
+        }
+        let node = slot.as_mut().unwrap();
+        if node.count > 1 {
+            node.count -= 1;
+            return true;
+        }
+        if node.left.is_some() && node.right.is_some() {
+            // two children: the in-order successor takes this node's place
+            let mut next = Self::take_min(&mut node.right);
+            std::mem::swap(&mut node.payload, &mut next.payload);
+            node.count = next.count;
+            return true;
+        }
+        // leaf or one child: the child (if any) replaces the node
+        let child = node.left.take().or(node.right.take());
+        *slot = child;
+        true
+    }
+
+    // remove one occurrence of `data`, returning false if it isn't there.
+    // A counted node just has its count decremented.
+    // The root always has a payload, so a lone root node cannot be removed.
+    fn remove(&mut self, data: &T) -> bool {
+        if *data < *self.payload() {
+            Self::remove_from(&mut self.left, data)
+        } else if *data > *self.payload() {
+            Self::remove_from(&mut self.right, data)
+        } else if self.count > 1 {
+            self.count -= 1;
+            true
+        } else if self.left.is_some() && self.right.is_some() {
+            let mut next = Self::take_min(&mut self.right);
+            std::mem::swap(&mut self.payload, &mut next.payload);
+            self.count = next.count;
+            true
         } else {
-            match self.right {
-            Some(ref mut n) => n.insert(data),
-            None => self.set_right(Self::new(data)),
-            }            
+            match self.left.take().or(self.right.take()) {
+                Some(child) => { *self = *child; true },
+                None => false
+            }
+        }
+    }
+
+    // the value stored in this node
+    fn payload(&self) -> &T {
+        self.payload.as_ref().expect("node has no payload")
+    }
+
+    // consume the tree with an in-order walk, using an explicit stack
+    fn to_sorted_vec(self) -> Vec<T> {
+        let mut res = Vec::new();
+        let mut stack = Vec::new();
+        let mut cur = Some(Box::new(self));
+        loop {
+            while let Some(mut node) = cur {
+                cur = node.left.take();
+                stack.push(node);
+            }
+            match stack.pop() {
+                Some(mut node) => {
+                    cur = node.right.take();
+                    // Node has a Drop impl, so the payload can't be moved out of it.
+                    // But it can be taken out of its Option
+                    res.push(node.payload.take().expect("node has no payload"));
+                },
+                None => break
+            }
+        }
+        res
+    }
+
+    // smallest value: keep going left
+    fn min(&self) -> &T {
+        let mut node = self;
+        while let Some(ref left) = node.left {
+            node = left;
+        }
+        node.payload()
+    }
+
+    // largest value: keep going right
+    fn max(&self) -> &T {
+        let mut node = self;
+        while let Some(ref right) = node.right {
+            node = right;
+        }
+        node.payload()
+    }
+
+    // smallest value greater than `key`, which need not be in the tree.
+    // Going left, the current node is the best candidate so far
+    fn successor(&self, key: &T) -> Option<&T> {
+        let mut best = None;
+        let mut cur = Some(self);
+        while let Some(node) = cur {
+            if *node.payload() > *key {
+                best = Some(node.payload());
+                cur = node.left.as_deref();
+            } else {
+                cur = node.right.as_deref();
+            }
+        }
+        best
+    }
+
+    // largest value less than `key`
+    fn predecessor(&self, key: &T) -> Option<&T> {
+        let mut best = None;
+        let mut cur = Some(self);
+        while let Some(node) = cur {
+            if *node.payload() < *key {
+                best = Some(node.payload());
+                cur = node.right.as_deref();
+            } else {
+                cur = node.left.as_deref();
+            }
+        }
+        best
+    }
+
+    // the values in [lo, hi], in order. An in-order walk with an explicit stack,
+    // which only goes left if smaller values could be in range, and only goes
+    // right if larger ones could (equal values are always inserted on the right)
+    fn range(&self, lo: &T, hi: &T) -> Vec<&T> {
+        let mut res = Vec::new();
+        let mut stack = Vec::new();
+        let mut cur = Some(self);
+        loop {
+            while let Some(node) = cur {
+                stack.push(node);
+                cur = if *node.payload() > *lo { node.left.as_deref() } else { None };
+            }
+            match stack.pop() {
+                Some(node) => {
+                    if *node.payload() >= *lo && *node.payload() <= *hi {
+                        res.push(node.payload());
+                    }
+                    cur = if *node.payload() <= *hi { node.right.as_deref() } else { None };
+                },
+                None => break
+            }
+        }
+        res
+    }
+
+    // breadth-first: each level in turn, left to right
+    fn level_order(&self) -> Vec<&T> {
+        let mut res = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(self);
+        while let Some(node) = queue.pop_front() {
+            res.push(node.payload());
+            if let Some(ref left) = node.left {
+                queue.push_back(left);
+            }
+            if let Some(ref right) = node.right {
+                queue.push_back(right);
+            }
+        }
+        res
+    }
+
+    // number of nodes in the tree. A worklist rather than recursion,
+    // since a degenerate tree may be very deep
+    fn len(&self) -> usize {
+        let mut count = 0;
+        let mut work = vec![self];
+        while let Some(node) = work.pop() {
+            count += 1;
+            work.extend(node.left.as_deref());
+            work.extend(node.right.as_deref());
+        }
+        count
+    }
+
+    // number of nodes on the longest path from here down to a leaf
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let mut work = vec![(self, 1)];
+        while let Some((node, depth)) = work.pop() {
+            height = std::cmp::max(height, depth);
+            work.extend(node.left.as_deref().map(|n| (n, depth + 1)));
+            work.extend(node.right.as_deref().map(|n| (n, depth + 1)));
+        }
+        height
+    }
+}
+
+// the first item becomes the root, so the iterator must not be empty
+impl <T: PartialOrd> FromIterator<T> for Node<T> {
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self {
+        let mut iter = iter.into_iter();
+        let mut root = Node::new(iter.next().expect("cannot build a tree from nothing"));
+        for x in iter {
+            root.insert(x);
+        }
+        root
+    }
+}
+
+// The default drop recurses through each Box, which can blow the stack
+// for a degenerate tree. So we unhook the children into a worklist instead.
+impl <T> Drop for Node<T> {
+    fn drop(&mut self) {
+        let mut work = Vec::new();
+        work.extend(self.left.take());
+        work.extend(self.right.take());
+        while let Some(mut node) = work.pop() {
+            work.extend(node.left.take());
+            work.extend(node.right.take());
         }
     }
 }
 
+// An AVL tree keeps itself balanced: at every node the heights of the two
+// subtrees differ by at most one, so the height is O(log n) even for sorted input.
+// Each node remembers its own height, and insert rotates nodes on the way back up.
+type AvlBox<T> = Option<Box<AvlNode<T>>>;
+
+#[derive(Debug)]
+struct AvlNode<T> {
+    payload: T,
+    height: usize,
+    left: AvlBox<T>,
+    right: AvlBox<T>
+}
+
+#[derive(Debug)]
+struct AvlTree<T> {
+    root: AvlBox<T>
+}
+
+impl <T: PartialOrd> AvlNode<T> {
+    fn height(n: &AvlBox<T>) -> usize {
+        n.as_ref().map_or(0, |node| node.height)
+    }
+
+    fn update_height(&mut self) {
+        self.height = 1 + std::cmp::max(Self::height(&self.left), Self::height(&self.right));
+    }
+
+    // positive if the left side is taller
+    fn balance(&self) -> isize {
+        Self::height(&self.left) as isize - Self::height(&self.right) as isize
+    }
+
+    //      n            l
+    //     / \          / \
+    //    l   c  ==>   a   n
+    //   / \              / \
+    //  a   b            b   c
+    fn rotate_right(mut n: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+        let mut l = n.left.take().expect("rotate_right needs a left child");
+        n.left = l.right.take();
+        n.update_height();
+        l.right = Some(n);
+        l.update_height();
+        l
+    }
+
+    // the mirror image of rotate_right
+    fn rotate_left(mut n: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+        let mut r = n.right.take().expect("rotate_left needs a right child");
+        n.right = r.left.take();
+        n.update_height();
+        r.left = Some(n);
+        r.update_height();
+        r
+    }
+
+    // after an insert below this node, restore the AVL property.
+    // If the taller child leans the other way, it has to be rotated first
+    fn rebalance(mut n: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+        n.update_height();
+        let balance = n.balance();
+        if balance > 1 {
+            if n.left.as_ref().unwrap().balance() < 0 {
+                n.left = Some(Self::rotate_left(n.left.take().unwrap()));
+            }
+            Self::rotate_right(n)
+        } else if balance < -1 {
+            if n.right.as_ref().unwrap().balance() > 0 {
+                n.right = Some(Self::rotate_right(n.right.take().unwrap()));
+            }
+            Self::rotate_left(n)
+        } else {
+            n
+        }
+    }
+
+    // recursion is fine here, since the depth is only O(log n)
+    fn insert(slot: AvlBox<T>, data: T) -> Box<AvlNode<T>> {
+        match slot {
+            None => Box::new(AvlNode{payload: data, height: 1, left: None, right: None}),
+            Some(mut n) => {
+                if data < n.payload {
+                    n.left = Some(Self::insert(n.left.take(), data));
+                } else {
+                    n.right = Some(Self::insert(n.right.take(), data));
+                }
+                Self::rebalance(n)
+            }
+        }
+    }
+
+    fn collect<'a>(n: &'a AvlBox<T>, res: &mut Vec<&'a T>) {
+        if let Some(ref node) = *n {
+            Self::collect(&node.left, res);
+            res.push(&node.payload);
+            Self::collect(&node.right, res);
+        }
+    }
+}
+
+impl <T: PartialOrd> AvlTree<T> {
+    fn new() -> AvlTree<T> {
+        AvlTree{root: None}
+    }
+
+    fn insert(&mut self, data: T) {
+        self.root = Some(AvlNode::insert(self.root.take(), data));
+    }
+
+    fn height(&self) -> usize {
+        AvlNode::height(&self.root)
+    }
+
+    // the values in order
+    fn values(&self) -> Vec<&T> {
+        let mut res = Vec::new();
+        AvlNode::collect(&self.root, &mut res);
+        res
+    }
+}
 
 fn main() {
     let mut root = Node::new("root".to_string());    
@@ -49,4 +437,131 @@ fn main() {
     root.insert("four".to_string());
 
     println!("root {:#?}",root);
+    println!("len {} height {}",root.len(),root.height());
+
+    let single = Node::new(1);
+    assert_eq!(single.len(), 1);
+    assert_eq!(single.height(), 1);
+
+    let mut balanced = Node::new(4);
+    for &x in &[2, 6, 1, 3, 5, 7] {
+        balanced.insert(x);
+    }
+    assert_eq!(balanced.len(), 7);
+    assert_eq!(*balanced.min(), 1);
+    assert_eq!(*balanced.max(), 7);
+    assert_eq!(balanced.height(), 3);
+
+    // sorted inserts give a degenerate tree - a linked list!
+    let mut chain = Node::new(0);
+    for x in 1..10 {
+        chain.insert(x);
+    }
+    assert_eq!(chain.len(), 10);
+    assert_eq!(chain.height(), chain.len());
+
+    // removing a leaf
+    assert!(balanced.remove(&7));
+    assert!(! balanced.remove(&7));
+    assert_eq!(balanced.len(), 6);
+    // an internal node with one child (6 now only has 5)
+    assert!(balanced.remove(&6));
+    assert_eq!(*balanced.right.as_ref().unwrap().payload(), 5);
+    // the root with two children is replaced by its successor
+    assert!(balanced.remove(&4));
+    assert_eq!(*balanced.payload(), 5);
+    assert_eq!(balanced.len(), 4);
+    assert!(! balanced.remove(&42));
+
+    let shape: Node<i32> = vec![4, 2, 6, 1, 3, 5, 7].into_iter().collect();
+    assert_eq!(shape.level_order(), [&4, &2, &6, &1, &3, &5, &7]);
+
+    // a chain of 100k nodes built bottom-up; dropping it must not overflow
+    let mut long = Node::new(100_000);
+    for x in (0..100_000).rev() {
+        let mut parent = Node::new(x);
+        parent.set_right(long);
+        long = parent;
+    }
+    drop(long);
+
+    // and the same again with insert, which no longer recurses
+    let mut sorted = Node::new(0);
+    for x in 1..50_000 {
+        sorted.insert(x);
+    }
+    assert_eq!(*sorted.max(), 49_999);
+    assert_eq!(sorted.len(), 50_000);
+    assert_eq!(sorted.height(), 50_000);
+    // removing from the far end of the chain must not recurse all the way down
+    assert!(sorted.remove(&49_999));
+    assert!(sorted.remove(&49_998));
+    assert!(! sorted.remove(&49_999));
+    assert!(sorted.remove(&25_000));
+    assert_eq!(*sorted.max(), 49_997);
+    assert_eq!(sorted.len(), 49_997);
+
+    let mut counted = Node::new("a");
+    counted.insert_counted("a");
+    counted.insert_counted("a");
+    assert_eq!(counted.len(), 1);
+    assert_eq!(counted.count, 3);
+    counted.insert_counted("b");
+    assert!(counted.remove(&"a"));
+    assert_eq!(counted.count, 2);
+
+    let t: Node<i32> = vec![5, 3, 8, 1].into_iter().collect();
+    assert_eq!(t.to_sorted_vec(), [1, 3, 5, 8]);
+
+    let nums: Node<i32> = vec![50, 20, 80, 10, 30, 60, 90, 25, 35, 30].into_iter().collect();
+    assert_eq!(nums.range(&25, &60), [&25, &30, &30, &35, &50, &60]);
+    assert_eq!(nums.range(&26, &29), Vec::<&i32>::new());
+    assert_eq!(nums.range(&0, &100).len(), nums.len());
+
+    // 10 20 25 30 30 35 50 60 80 90
+    assert_eq!(nums.successor(&30), Some(&35));
+    assert_eq!(nums.predecessor(&30), Some(&25));
+    assert_eq!(nums.successor(&51), Some(&60));
+    assert_eq!(nums.predecessor(&51), Some(&50));
+    assert_eq!(nums.successor(&5), Some(&10));
+    assert_eq!(nums.predecessor(&10), None);
+    assert_eq!(nums.successor(&90), None);
+    assert_eq!(nums.predecessor(&1000), Some(&90));
+
+    // sorted input that would make a chain of a plain tree
+    let mut avl = AvlTree::new();
+    for x in 1..=1000 {
+        avl.insert(x);
+    }
+    assert!(avl.height() < 20);
+    assert_eq!(avl.height(), 10);
+    assert!(avl.values().into_iter().cloned().eq(1..=1000));
+    let mut avl = AvlTree::new();
+    for x in (1..=1000).rev().chain(vec![500, 500]) {
+        avl.insert(x);
+    }
+    assert!(avl.height() <= 11);
+    assert_eq!(avl.values().len(), 1002);
+    assert_eq!(AvlTree::<i32>::new().height(), 0);
+
+    // count the comparisons, to see that subtrees are skipped
+    static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Probe(i32);
+
+    impl PartialOrd for Probe {
+        fn partial_cmp(&self, other: &Probe) -> Option<Ordering> {
+            COMPARISONS.fetch_add(1, AtomicOrdering::SeqCst);
+            self.0.partial_cmp(&other.0)
+        }
+    }
+
+    let probes: Node<Probe> = (0..1000).map(|i| Probe((i * 397) % 1000)).collect();
+    COMPARISONS.store(0, AtomicOrdering::SeqCst);
+    let found = probes.range(&Probe(100), &Probe(109));
+    assert_eq!(found.len(), 10);
+    assert_eq!(*found[0], Probe(100));
+    // a full walk would compare at every one of the 1000 nodes
+    assert!(COMPARISONS.load(AtomicOrdering::SeqCst) < 1000);
 }
@@ -1,6 +1,19 @@
 // test-json/src/main.rs
 extern crate json;
 
+// "payload.features" is doc["payload"]["features"], except that
+// a missing key gives None rather than Null
+fn get_path<'a>(doc: &'a json::JsonValue, path: &str) -> Option<&'a json::JsonValue> {
+    let mut current = doc;
+    for key in path.split('.') {
+        if ! current.has_key(key) {
+            return None;
+        }
+        current = &current[key];
+    }
+    Some(current)
+}
+
 fn main() {
     let mut doc = json::parse(r#"
     {
@@ -26,6 +39,11 @@ fn main() {
     
     assert_eq!(code, 200);
     assert_eq!(success, true);
+
+    let features = get_path(&doc, "payload.features").expect("no features");
+    assert_eq!(features.len(), 4);
+    assert!(get_path(&doc, "payload.bugs").is_none());
+    assert!(get_path(&doc, "code.number").is_none());
     
     //~ println!("debug {:?}",doc);
     //~ println!("display {}",doc);
@@ -1,7 +1,149 @@
 // thread10.rs
 use std::thread;
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex, Condvar};
+use std::collections::VecDeque;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
+fn shell(cmd: &str) -> (String,bool) {
+    let cmd = format!("{} 2>&1",cmd);
+    let output = Command::new("/bin/sh")
+        .arg("-c")
+        .arg(&cmd)
+        .output()
+        .expect("no shell?");
+    (
+        String::from_utf8_lossy(&output.stdout).trim_end().to_string(),
+        output.status.success()
+    )
+}
+
+// A queue shared between threads, holding at most `capacity` items.
+// `send` blocks while the queue is full, `recv` blocks while it is empty.
+// Once closed, `recv` drains what is left and then returns None.
+struct Channel<T> {
+    state: Mutex<ChannelState<T>>,
+    cvar: Condvar,
+    capacity: usize
+}
+
+struct ChannelState<T> {
+    queue: VecDeque<T>,
+    closed: bool
+}
+
+impl <T> Channel<T> {
+    fn new(capacity: usize) -> Channel<T> {
+        assert!(capacity > 0, "channel capacity must be positive");
+        Channel {
+            state: Mutex::new(ChannelState{queue: VecDeque::new(), closed: false}),
+            cvar: Condvar::new(),
+            capacity: capacity
+        }
+    }
+
+    // gives the item back if the channel is closed
+    fn send(&self, item: T) -> Result<(),T> {
+        let mut state = self.state.lock().unwrap();
+        while state.queue.len() >= self.capacity && ! state.closed {
+            state = self.cvar.wait(state).unwrap();
+        }
+        if state.closed {
+            return Err(item);
+        }
+        state.queue.push_back(item);
+        // senders and receivers share the condition variable, so wake everyone
+        self.cvar.notify_all();
+        Ok(())
+    }
+
+    fn recv(&self) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        while state.queue.is_empty() && ! state.closed {
+            state = self.cvar.wait(state).unwrap();
+        }
+        let item = state.queue.pop_front();
+        self.cvar.notify_all();
+        item
+    }
+
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.cvar.notify_all();
+    }
+}
+
+// Runs shell commands on a fixed number of worker threads.
+// Idle workers wait on the channel until a command is queued,
+// or until the pool is closed.
+struct ThreadPool {
+    jobs: Arc<Channel<String>>,
+    results: Arc<Mutex<Vec<(String,bool)>>>,
+    workers: Vec<thread::JoinHandle<()>>
+}
+
+impl ThreadPool {
+    fn new(nthreads: usize) -> ThreadPool {
+        let jobs: Arc<Channel<String>> = Arc::new(Channel::new(64));
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let mut workers = Vec::new();
+        for _ in 0..nthreads {
+            let jobs = jobs.clone();
+            let results = results.clone();
+            workers.push(thread::spawn(move || {
+                // None means closed, and nothing left to do
+                while let Some(cmd) = jobs.recv() {
+                    let res = shell(&cmd);
+                    results.lock().unwrap().push(res);
+                }
+            }));
+        }
+        ThreadPool{jobs: jobs, results: results, workers: workers}
+    }
+
+    fn execute(&self, cmd: &str) {
+        self.jobs.send(cmd.to_string()).expect("pool is closed");
+    }
+
+    // no more commands: wait for the workers to finish the queue, and collect the outputs
+    fn join(self) -> Vec<(String,bool)> {
+        self.jobs.close();
+        for w in self.workers {
+            w.join().expect("worker failed");
+        }
+        let results = self.results.lock().unwrap();
+        results.clone()
+    }
+}
+
+// A counting semaphore: at most `count` threads can hold it at once.
+// Share it between threads with an Arc.
+struct Semaphore {
+    count: Mutex<isize>,
+    cvar: Condvar
+}
+
+impl Semaphore {
+    fn new(count: isize) -> Semaphore {
+        Semaphore{count: Mutex::new(count), cvar: Condvar::new()}
+    }
+
+    // block until the count is positive, then take one
+    fn acquire(&self) {
+        let mut count = self.count.lock().unwrap();
+        while *count <= 0 {
+            count = self.cvar.wait(count).unwrap();
+        }
+        *count -= 1;
+    }
+
+    fn release(&self) {
+        *self.count.lock().unwrap() += 1;
+        self.cvar.notify_one();
+    }
+}
 
 fn main() {
     let (tx, rx) = mpsc::sync_channel(0);
@@ -17,4 +159,69 @@ fn main() {
     }
     t1.join().unwrap();
 
+    let pool = ThreadPool::new(3);
+    for i in 0..8 {
+        pool.execute(&format!("echo job {}",i));
+    }
+    pool.execute("false");
+    let mut results = pool.join();
+    results.sort();
+    assert_eq!(results.len(), 9);
+    assert_eq!(results[0], ("".to_string(), false));
+    assert_eq!(results[1], ("job 0".to_string(), true));
+    for res in results {
+        println!("{:?}",res);
+    }
+
+    // one producer, two consumers, through a small channel
+    let chan = Arc::new(Channel::new(4));
+    let consumers: Vec<_> = (0..2).map(|_| {
+        let chan = chan.clone();
+        thread::spawn(move || {
+            let mut got = Vec::new();
+            while let Some(i) = chan.recv() {
+                got.push(i);
+            }
+            got
+        })
+    }).collect();
+    for i in 0..100 {
+        chan.send(i).unwrap();
+    }
+    chan.close();
+    assert_eq!(chan.send(100), Err(100));
+    let mut all = Vec::new();
+    for c in consumers {
+        let got = c.join().unwrap();
+        // each consumer sees the items in the order they were sent
+        assert!(got.windows(2).all(|w| w[0] < w[1]));
+        all.extend(got);
+    }
+    all.sort();
+    assert_eq!(all, (0..100).collect::<Vec<_>>());
+    assert_eq!(chan.recv(), None);
+
+    let n = 3;
+    let sema = Arc::new(Semaphore::new(n as isize));
+    let inside = Arc::new(AtomicUsize::new(0));
+    let most_inside = Arc::new(AtomicUsize::new(0));
+    let mut threads = Vec::new();
+    for _ in 0..n+2 {
+        let sema = sema.clone();
+        let inside = inside.clone();
+        let most_inside = most_inside.clone();
+        threads.push(thread::spawn(move || {
+            sema.acquire();
+            let now = inside.fetch_add(1, Ordering::SeqCst) + 1;
+            most_inside.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            inside.fetch_sub(1, Ordering::SeqCst);
+            sema.release();
+        }));
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+    println!("at most {} threads inside",most_inside.load(Ordering::SeqCst));
+    assert!(most_inside.load(Ordering::SeqCst) <= n);
 }
@@ -11,6 +11,18 @@ impl MyString {
     }
 }
 
+// one thread per item. The threads may finish in any order, but
+// joining the handles in the order they were spawned keeps the results in order.
+fn parallel_map<T,R,F>(items: Vec<T>, f: F) -> Vec<R>
+where T: Send + 'static, R: Send + 'static, F: Fn(T) -> R + Send + Sync + 'static {
+    let f = Arc::new(f);
+    let threads: Vec<_> = items.into_iter().map(|item| {
+        let f = f.clone();
+        thread::spawn(move || f(item))
+    }).collect();
+    threads.into_iter().map(|t| t.join().expect("thread failed")).collect()
+}
+
 fn main() {
     let mut threads = Vec::new();
     let name = Arc::new(MyString::new("dolly"));
@@ -26,4 +38,7 @@ fn main() {
     for t in threads {
         t.join().expect("thread failed");
     }
+
+    let squares = parallel_map(vec![1,2,3,4], |x| x*x);
+    assert_eq!(squares, [1,4,9,16]);
 }
@@ -41,6 +41,121 @@ where T: Sized, I: Iterator<Item=T> {
     }
 }
 
+pub trait ToSortedVec {
+    type Item;
+    fn to_sorted_vec(self) -> Vec<Self::Item>
+    where Self::Item: Ord;
+    fn to_sorted_vec_by<F>(self, cmp: F) -> Vec<Self::Item>
+    where F: FnMut(&Self::Item,&Self::Item) -> Ordering;
+}
+
+use std::cmp::Ordering;
+
+impl <I> ToSortedVec for I
+where I: Iterator {
+    type Item = I::Item;
+
+    fn to_sorted_vec(self) -> Vec<Self::Item>
+    where Self::Item: Ord {
+        let mut v: Vec<_> = self.collect();
+        v.sort();
+        v
+    }
+
+    fn to_sorted_vec_by<F>(self, cmp: F) -> Vec<Self::Item>
+    where F: FnMut(&Self::Item,&Self::Item) -> Ordering {
+        let mut v: Vec<_> = self.collect();
+        v.sort_by(cmp);
+        v
+    }
+}
+
+pub trait ToMap {
+    type Key;
+    type Value;
+    fn to_map(self) -> HashMap<Self::Key,Self::Value>;
+}
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl <K,V,I> ToMap for I
+where K: Eq + Hash, I: Iterator<Item=(K,V)> {
+    type Key = K;
+    type Value = V;
+
+    fn to_map(self) -> HashMap<K,V> {
+        FromIterator::from_iter(self)
+    }
+}
+
+// Overlapping windows of n items, like slice::windows but for any iterator.
+// The items are cloned, since each one appears in up to n windows
+pub trait Windowed: Iterator + Sized {
+    fn windowed(self, n: usize) -> WindowedIter<Self>;
+}
+
+pub struct WindowedIter<I: Iterator> {
+    iter: std::iter::Fuse<I>,
+    window: VecDeque<I::Item>,
+    n: usize
+}
+
+use std::collections::VecDeque;
+
+impl <I> Windowed for I
+where I: Iterator, I::Item: Clone {
+    fn windowed(self, n: usize) -> WindowedIter<Self> {
+        assert!(n > 0, "window size must be positive");
+        WindowedIter{iter: self.fuse(), window: VecDeque::with_capacity(n), n: n}
+    }
+}
+
+impl <I> Iterator for WindowedIter<I>
+where I: Iterator, I::Item: Clone {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the first time, fill the window; after that, slide it along by one
+        if self.window.len() == self.n {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.n {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
+// Non-overlapping chunks of up to n items; the last chunk may be shorter
+pub trait Chunked: Iterator + Sized {
+    fn chunked(self, n: usize) -> Chunks<Self>;
+}
+
+pub struct Chunks<I> {
+    iter: I,
+    n: usize
+}
+
+impl <I: Iterator> Chunked for I {
+    fn chunked(self, n: usize) -> Chunks<Self> {
+        assert!(n > 0, "chunk size must be positive");
+        Chunks{iter: self, n: n}
+    }
+}
+
+impl <I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = self.iter.by_ref().take(self.n).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
 
 fn main() {
     for x in range(0.0, 1.0, 0.1) {
@@ -52,4 +167,24 @@ fn main() {
 
     let v = range(0.0, 1.0, 0.1).to_vec();
     println!("{:?}",v);
+
+    let m = [("a",1),("b",2)].iter().cloned().to_map();
+    assert_eq!(m.get("a"), Some(&1));
+    assert_eq!(m.get("b"), Some(&2));
+    assert_eq!(m.get("c"), None);
+
+    let words = "the quick brown fox".split_whitespace();
+    assert_eq!(words.clone().to_sorted_vec(), ["brown", "fox", "quick", "the"]);
+    assert_eq!(words.to_sorted_vec_by(|a,b| b.cmp(a)), ["the", "quick", "fox", "brown"]);
+
+    let pairs: Vec<_> = (1..=4).windowed(2).collect();
+    assert_eq!(pairs, [[1,2], [2,3], [3,4]]);
+    assert_eq!((1..=2).windowed(3).count(), 0);
+    let bigrams: Vec<_> = "a rose is a rose".split_whitespace().windowed(2).collect();
+    assert_eq!(bigrams[1], ["rose", "is"]);
+
+    let chunks: Vec<_> = (1..=5).chunked(2).collect();
+    assert_eq!(chunks, [vec![1,2], vec![3,4], vec![5]]);
+    assert_eq!((1..=4).chunked(2).last(), Some(vec![3,4]));
+    assert_eq!((1..1).chunked(2).count(), 0);
 }